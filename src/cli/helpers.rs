@@ -7,36 +7,32 @@ use crate::config::validator::validate_config;
 use crate::config::FileConfig;
 
 pub fn load_config(file_path: &str) -> FileConfig {
-    let file = File::open(file_path).unwrap_or_else(|err| {
-        eprintln!("ERROR: Failed to open file {}.\n{}", file_path, err);
-        std::process::exit(1);
-    });
+    match read_config(file_path) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Reads, parses and validates the config file. Warnings are printed to stderr.
+pub fn read_config(file_path: &str) -> Result<FileConfig, String> {
+    let file = File::open(file_path)
+        .map_err(|err| format!("Failed to open file {}.\n{}", file_path, err))?;
 
     let mut buf_reader = BufReader::new(file);
     let mut content = String::new();
     buf_reader
         .read_to_string(&mut content)
-        .expect("Failed to read from file");
+        .map_err(|err| format!("Failed to read from file {}.\n{}", file_path, err))?;
 
-    let config = match parse_config(&content) {
-        Ok(config) => config,
-        Err(err) => {
-            eprintln!("ERROR: {}", err);
-            std::process::exit(1);
-        }
-    };
+    let config = parse_config(&content).map_err(|err| err.to_string())?;
 
-    match validate_config(&config) {
-        Ok(warnings) => {
-            for warning in warnings {
-                eprintln!("WARNING: {}", warning);
-            }
-        }
-        Err(err) => {
-            eprintln!("ERROR: {}", err);
-            std::process::exit(1);
-        }
+    let warnings = validate_config(&config).map_err(|err| err.to_string())?;
+    for warning in warnings {
+        eprintln!("WARNING: {}", warning);
     }
 
-    config
+    Ok(config)
 }
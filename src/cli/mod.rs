@@ -2,9 +2,11 @@ use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
 mod helpers;
+mod silence;
 mod test;
 mod watch;
 
+use silence::SilenceOpts;
 use test::TestOpts;
 use watch::WatchOpts;
 
@@ -18,6 +20,10 @@ enum Command {
     /// Test checkers and notifiers
     #[structopt(name = "test")]
     Test(TestOpts),
+
+    /// Suppress notifications of a running watcher for a while
+    #[structopt(name = "silence")]
+    Silence(SilenceOpts),
 }
 
 pub fn run() {
@@ -26,5 +32,6 @@ pub fn run() {
     match command {
         Command::Watch(opts) => watch::run(opts),
         Command::Test(opts) => test::run(opts),
+        Command::Silence(opts) => silence::run(opts),
     };
 }
@@ -0,0 +1,85 @@
+use structopt::StructOpt;
+
+// Manages silences through the API of a running `hellcheck watch`.
+#[derive(StructOpt, Debug)]
+pub struct SilenceOpts {
+    /// Address of the built-in server, e.g. http://127.0.0.1:8080
+    #[structopt(short = "s", long = "server")]
+    server: String,
+
+    /// `admin_token` of the server
+    #[structopt(long = "token", env = "HELLCHECK_ADMIN_TOKEN")]
+    token: String,
+
+    /// How long to suppress notifications, e.g. 30m
+    #[structopt(
+        short = "d",
+        long = "duration",
+        raw(required_unless_one = r#"&["cancel", "list"]"#)
+    )]
+    duration: Option<String>,
+
+    /// Silence the checker with the given id (repeatable)
+    #[structopt(short = "c", long = "checker")]
+    checkers: Vec<String>,
+
+    /// Silence the checkers with the given tag (repeatable)
+    #[structopt(short = "t", long = "tag")]
+    tags: Vec<String>,
+
+    /// Why notifications are silenced
+    #[structopt(long = "comment")]
+    comment: Option<String>,
+
+    /// Do not notify about the state of the checkers when the silence ends
+    #[structopt(long = "no-summary")]
+    no_summary: bool,
+
+    /// Remove the silence with the given id
+    #[structopt(long = "cancel", conflicts_with = "list")]
+    cancel: Option<u64>,
+
+    /// List active silences
+    #[structopt(long = "list")]
+    list: bool,
+}
+
+pub fn run(opts: SilenceOpts) {
+    let url = format!("{}/api/silences", opts.server.trim_end_matches('/'));
+    let client = reqwest::Client::new();
+
+    let request = if opts.list {
+        client.get(&url)
+    } else if let Some(id) = opts.cancel {
+        client.delete(&format!("{}/{}", url, id))
+    } else {
+        let mut query: Vec<(&str, String)> = vec![];
+        // unwrap is safe here, because structopt requires duration unless cancel or list is given
+        query.push(("duration", opts.duration.clone().unwrap()));
+        query.extend(opts.checkers.iter().map(|c| ("checker", c.clone())));
+        query.extend(opts.tags.iter().map(|t| ("tag", t.clone())));
+        if let Some(ref comment) = opts.comment {
+            query.push(("comment", comment.clone()));
+        }
+        if opts.no_summary {
+            query.push(("summary", "false".to_owned()));
+        }
+        client.post(&url).query(&query)
+    };
+
+    let result = request
+        .bearer_auth(&opts.token)
+        .send()
+        .and_then(|mut resp| resp.text().map(|text| (resp.status(), text)));
+    match result {
+        Ok((status, text)) if status.is_success() => println!("{}", text),
+        Ok((status, text)) => {
+            eprintln!("ERROR: {} {}", status, text);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,11 +1,156 @@
 use structopt::StructOpt;
 
+use std::time::SystemTime;
+
+use crate::cli::helpers::load_config;
+use crate::config::{CheckerConfig, FileConfig};
+use crate::notifiers::format_duration;
+use crate::reactor::{build_notification, build_notifiers, Reason, State, StateMessage};
+use crate::watcher::{check_once, CheckResult};
+
 #[derive(StructOpt, Debug)]
 pub struct TestOpts {
     #[structopt(short = "f", long = "file")]
     file: String,
+
+    /// Send a synthetic notification with the given state through every notifier
+    #[structopt(
+        short = "n",
+        long = "notify",
+        raw(possible_values = r#"&["up", "degraded", "down"]"#)
+    )]
+    notify: Option<String>,
+}
+
+pub fn run(opts: TestOpts) {
+    let config = load_config(&opts.file);
+
+    let results = check_once(&config);
+    let polled_checkers: Vec<CheckerConfig> = config
+        .checkers
+        .iter()
+        .filter(|c| c.is_polled())
+        .cloned()
+        .collect();
+    print_check_results(&polled_checkers, &results);
+    // Degraded services are reported, but do not fail the test
+    let mut success = results.iter().all(|r| r.state != State::Down);
+
+    if let Some(ref state_name) = opts.notify {
+        let state = match state_name.as_ref() {
+            "up" => State::Up,
+            "degraded" => State::Degraded,
+            _ => State::Down,
+        };
+        println!();
+        success &= test_notifiers(&config, state);
+    }
+
+    if !success {
+        std::process::exit(1);
+    }
+}
+
+fn print_check_results(checkers: &[CheckerConfig], results: &[CheckResult]) {
+    let rows: Vec<Vec<String>> = checkers
+        .iter()
+        .zip(results.iter())
+        .map(|(checker, result)| {
+            vec![
+                checker.id.clone(),
+                format_state(&result.state),
+                result
+                    .response_time
+                    .map(format_duration)
+                    .unwrap_or_default(),
+                result
+                    .reason
+                    .as_ref()
+                    .map(|r| r.to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_table(&["CHECKER", "STATUS", "LATENCY", "REASON"], &rows);
+}
+
+// Sends a synthetic notification through every declared notifier.
+// Returns true if all the notifiers succeeded.
+fn test_notifiers(config: &FileConfig, state: State) -> bool {
+    let notifiers = build_notifiers(config);
+    let mut success = true;
+    let mut rows: Vec<Vec<String>> = vec![];
+
+    for notifier_config in config.notifiers.iter() {
+        let notifier = &notifiers[&notifier_config.id];
+
+        // Pretend the notification comes from a checker that uses the notifier,
+        // so the message looks exactly like a real one. An unused notifier gets the first
+        // checker, there is at least one, because the config is validated.
+        let checker = config
+            .checkers
+            .iter()
+            .find(|c| c.uses_notifier(&notifier_config.id))
+            .unwrap_or(&config.checkers[0]);
+        let reason = match state {
+            State::Up => None,
+            State::Degraded | State::Down => Some(Reason::Other(
+                "Test notification sent by `hellcheck test`".to_owned(),
+            )),
+        };
+        let msg = StateMessage {
+            checker_id: checker.id.clone(),
+            state: state.clone(),
+            reason,
+            http_status: None,
+            response_time: None,
+            certificate_days_left: None,
+            timestamp: SystemTime::now(),
+        };
+        let notification = build_notification(checker, &msg);
+
+        let result = match notifier.notify(&notification) {
+            Ok(_) => "OK".to_owned(),
+            Err(_) => {
+                success = false;
+                "FAILED".to_owned()
+            }
+        };
+        rows.push(vec![notifier_config.id.clone(), result]);
+    }
+
+    print_table(&["NOTIFIER", "RESULT"], &rows);
+    success
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    print_row(&header, &widths);
+    for row in rows.iter() {
+        print_row(row, &widths);
+    }
+}
+
+fn print_row(cells: &[String], widths: &[usize]) {
+    let line: Vec<String> = cells
+        .iter()
+        .zip(widths.iter())
+        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+        .collect();
+    println!("{}", line.join("  ").trim_end());
 }
 
-pub fn run(_opts: TestOpts) {
-    eprintln!("test command is not implemented yet");
+fn format_state(state: &State) -> String {
+    match state {
+        State::Up => "UP".to_owned(),
+        State::Degraded => "DEGRADED".to_owned(),
+        State::Down => "DOWN".to_owned(),
+    }
 }
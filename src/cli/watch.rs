@@ -1,20 +1,123 @@
 use structopt::StructOpt;
 
-use std::sync::mpsc;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, SystemTime};
 
-use crate::cli::helpers::load_config;
-use crate::reactor::StateMessage;
+use crate::cli::helpers::{load_config, read_config};
+use crate::config::FileConfig;
+use crate::maintenance::SharedSilences;
+use crate::metrics::{spawn_server, Metrics};
+use crate::reactor::Message;
+
+// How often to check for SIGHUP and modifications of the config file.
+const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(StructOpt, Debug)]
 pub struct WatchOpts {
     #[structopt(short = "f", long = "file")]
     file: String,
+
+    /// Reload the config file when it changes (it is always reloaded on SIGHUP)
+    #[structopt(long = "reload-on-change")]
+    reload_on_change: bool,
+
+    /// JSON file to persist states of the checkers across restarts
+    #[structopt(long = "state-file", parse(from_os_str))]
+    state_file: Option<PathBuf>,
 }
 
 pub fn run(opts: WatchOpts) {
     let config = load_config(&opts.file);
 
-    let (sender, receiver) = mpsc::channel::<StateMessage>();
-    crate::reactor::spawn(receiver, config.clone());
-    crate::watcher::run(config, sender);
+    let (sender, receiver) = mpsc::channel::<Message>();
+    let (reload_sender, reload_receiver) = futures::sync::mpsc::unbounded::<FileConfig>();
+
+    let metrics = Metrics::default();
+    if let Some(ref metrics_config) = config.metrics {
+        spawn_server(metrics_config, metrics.clone());
+    }
+
+    let silences = SharedSilences::default();
+    let states = crate::reactor::spawn(
+        receiver,
+        config.clone(),
+        opts.state_file.clone(),
+        metrics.clone(),
+        Arc::clone(&silences),
+    );
+    if let Some(ref server_config) = config.server {
+        crate::server::spawn(server_config, states, silences, sender.clone());
+    }
+    spawn_reloader(opts, config.clone(), sender.clone(), reload_sender);
+    crate::watcher::run(config, sender, reload_receiver, metrics);
+}
+
+// Reloads the config file on SIGHUP and, if requested, when the file is modified.
+// Invalid config is reported and ignored, so hellcheck keeps running with the previous one.
+fn spawn_reloader(
+    opts: WatchOpts,
+    mut config: FileConfig,
+    reactor_sender: mpsc::Sender<Message>,
+    watcher_sender: futures::sync::mpsc::UnboundedSender<FileConfig>,
+) {
+    let sighup = Arc::new(AtomicBool::new(false));
+    if let Err(err) = signal_hook::flag::register(signal_hook::SIGHUP, Arc::clone(&sighup)) {
+        eprintln!("WARNING: Failed to register SIGHUP handler: {}", err);
+    }
+
+    std::thread::spawn(move || {
+        let mut modified_at = get_modified_at(&opts.file);
+
+        loop {
+            std::thread::sleep(RELOAD_POLL_INTERVAL);
+
+            let mut reload = sighup.swap(false, Ordering::Relaxed);
+            if opts.reload_on_change {
+                let new_modified_at = get_modified_at(&opts.file);
+                if new_modified_at != modified_at {
+                    modified_at = new_modified_at;
+                    reload = true;
+                }
+            }
+            if !reload {
+                continue;
+            }
+
+            info!("Reloading config file {}", opts.file);
+            match read_config(&opts.file) {
+                Ok(new_config) => {
+                    warn_about_restart(&config, &new_config);
+                    reactor_sender
+                        .send(Message::Reload(new_config.clone()))
+                        .unwrap();
+                    watcher_sender.unbounded_send(new_config.clone()).unwrap();
+                    config = new_config;
+                    info!("Config file {} is reloaded", opts.file);
+                }
+                Err(err) => {
+                    eprintln!(
+                        "ERROR: Failed to reload config file, the previous config is kept.\n{}",
+                        err
+                    );
+                }
+            }
+        }
+    });
+}
+
+// Built-in servers are started once, so changes of their sections are not applied by a reload.
+fn warn_about_restart(config: &FileConfig, new_config: &FileConfig) {
+    if config.metrics != new_config.metrics {
+        eprintln!("WARNING: Changes of `metrics` section take effect only after restart");
+    }
+    if config.server != new_config.server {
+        eprintln!("WARNING: Changes of `server` section take effect only after restart");
+    }
+}
+
+fn get_modified_at(file_path: &str) -> Option<SystemTime> {
+    fs::metadata(file_path).and_then(|m| m.modified()).ok()
 }
@@ -3,6 +3,10 @@ mod types;
 pub mod validator;
 
 pub use self::types::{
-    BasicAuth, CheckerConfig, CommandNotifierConfig, FileConfig, HipchatNotifierConfig, Notifier,
-    NotifierConfig, SlackNotifierConfig, TelegramNotifierConfig,
+    Auth, BasicAuth, BodyAssertion, CheckerConfig, CheckerKind, CommandCheckerConfig,
+    CommandNotifierConfig, DnsCheckerConfig, DnsRecordType, EscalationStep, FileConfig,
+    HeartbeatCheckerConfig, HipchatNotifierConfig, HttpCheckerConfig, JsonAssertion, JsonOperator,
+    MaintenanceSchedule, MaintenanceScope, MaintenanceWindow, MetricsConfig, Notifier,
+    NotifierConfig, OAuth2Config, ServerConfig, SlackNotifierConfig, StatusRange, TcpCheckerConfig,
+    TelegramNotifierConfig, TlsCheckerConfig, Transition,
 };
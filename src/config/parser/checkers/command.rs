@@ -0,0 +1,47 @@
+use yaml_rust::yaml::Hash;
+
+use super::common::{parse_key, parse_yaml_to_vec, Result};
+use crate::config::CommandCheckerConfig;
+use crate::error::ConfigError;
+
+pub fn parse(id: &str, attrs: &Hash) -> Result<CommandCheckerConfig> {
+    let mut config_opt: Option<CommandCheckerConfig> = None;
+
+    for (attr_yaml_key, attr_yaml_val) in attrs {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        match attr_key.as_ref() {
+            "command" => match parse_yaml_to_vec(attr_yaml_val) {
+                Ok(vals) => {
+                    if let Some((command, arguments)) = vals.split_first() {
+                        let config = CommandCheckerConfig {
+                            command: command.clone(),
+                            arguments: arguments.to_vec(),
+                        };
+                        config_opt = Some(config);
+                    } else {
+                        let message =
+                            format!("`checkers.{}.command` must have a command specified", id);
+                        return Err(ConfigError::GeneralError { message });
+                    }
+                }
+                Err(_) => {
+                    let message = format!("`checkers.{}.command` must be an array.", id);
+                    return Err(ConfigError::GeneralError { message });
+                }
+            },
+            _ => {
+                let err = ConfigError::UnknownCheckerAttribute {
+                    checker_id: id.to_owned(),
+                    attr_name: attr_key,
+                };
+                return Err(err);
+            }
+        }
+    }
+
+    let config = config_opt.ok_or(ConfigError::FieldMissing {
+        path: format!("checkers.{}.command", id),
+    })?;
+    Ok(config)
+}
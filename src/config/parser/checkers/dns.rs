@@ -0,0 +1,85 @@
+use yaml_rust::yaml::Hash;
+
+use std::net::{IpAddr, SocketAddr};
+
+use super::common::{parse_key, parse_yaml_to_string, parse_yaml_to_string_or_vec, Result};
+use crate::config::{DnsCheckerConfig, DnsRecordType};
+use crate::error::ConfigError;
+
+const DNS_PORT: u16 = 53;
+
+pub fn parse(id: &str, attrs: &Hash) -> Result<DnsCheckerConfig> {
+    let mut name: Option<String> = None;
+    let mut record_type = DnsRecordType::A;
+    let mut nameserver: Option<SocketAddr> = None;
+    let mut expected: Vec<String> = vec![];
+
+    for (attr_yaml_key, attr_yaml_val) in attrs {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        match attr_key.as_ref() {
+            "name" => {
+                name = Some(parse_yaml_to_string(attr_yaml_val)?);
+            }
+            "record_type" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                record_type = match attr_val.to_uppercase().as_ref() {
+                    "A" => DnsRecordType::A,
+                    "AAAA" => DnsRecordType::Aaaa,
+                    "CNAME" => DnsRecordType::Cname,
+                    "MX" => DnsRecordType::Mx,
+                    "TXT" => DnsRecordType::Txt,
+                    _ => {
+                        let e = ConfigError::InvalidCheckerRecordType {
+                            checker_id: id.to_owned(),
+                            record_type: attr_val,
+                        };
+                        return Err(e);
+                    }
+                };
+            }
+            "nameserver" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                match parse_nameserver(&attr_val) {
+                    Some(addr) => nameserver = Some(addr),
+                    None => {
+                        let e = ConfigError::InvalidCheckerNameserver {
+                            checker_id: id.to_owned(),
+                            nameserver: attr_val,
+                        };
+                        return Err(e);
+                    }
+                }
+            }
+            "expected" => {
+                expected = parse_yaml_to_string_or_vec(attr_yaml_val)?;
+            }
+            _ => {
+                let err = ConfigError::UnknownCheckerAttribute {
+                    checker_id: id.to_owned(),
+                    attr_name: attr_key,
+                };
+                return Err(err);
+            }
+        }
+    }
+
+    let name = name.ok_or(ConfigError::FieldMissing {
+        path: format!("checkers.{}.name", id),
+    })?;
+
+    Ok(DnsCheckerConfig {
+        name,
+        record_type,
+        nameserver,
+        expected,
+    })
+}
+
+// Accepts `8.8.8.8`, `8.8.8.8:5353`, `::1` or `[::1]:5353`.
+fn parse_nameserver(value: &str) -> Option<SocketAddr> {
+    if let Ok(ip) = value.parse::<IpAddr>() {
+        return Some(SocketAddr::new(ip, DNS_PORT));
+    }
+    value.parse::<SocketAddr>().ok()
+}
@@ -0,0 +1,42 @@
+use yaml_rust::yaml::Hash;
+
+use std::time::Duration;
+
+use super::common::{parse_key, Result};
+use super::parse_duration;
+use crate::config::HeartbeatCheckerConfig;
+use crate::error::ConfigError;
+
+// Default is 1 min
+const DEFAULT_GRACE: Duration = Duration::from_secs(60);
+
+pub fn parse(id: &str, attrs: &Hash) -> Result<HeartbeatCheckerConfig> {
+    let mut period: Option<Duration> = None;
+    let mut grace = DEFAULT_GRACE;
+
+    for (attr_yaml_key, attr_yaml_val) in attrs {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        match attr_key.as_ref() {
+            "period" => {
+                period = Some(parse_duration(id, &attr_key, attr_yaml_val)?);
+            }
+            "grace" => {
+                grace = parse_duration(id, &attr_key, attr_yaml_val)?;
+            }
+            _ => {
+                let err = ConfigError::UnknownCheckerAttribute {
+                    checker_id: id.to_owned(),
+                    attr_name: attr_key,
+                };
+                return Err(err);
+            }
+        }
+    }
+
+    let period = period.ok_or(ConfigError::FieldMissing {
+        path: format!("checkers.{}.period", id),
+    })?;
+
+    Ok(HeartbeatCheckerConfig { period, grace })
+}
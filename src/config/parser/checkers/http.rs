@@ -1,94 +1,381 @@
-use hyper::Uri;
-use yaml_rust::yaml::Yaml;
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::{HeaderMap, Method, Uri};
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use yaml_rust::yaml::{Hash, Yaml};
 
-use std::time::Duration;
+use std::collections::HashMap;
 
-use super::common::{parse_key, parse_yaml_to_string, parse_yaml_to_vec, Result};
-use crate::config::{BasicAuth, CheckerConfig};
+use super::common::{
+    parse_key, parse_yaml_to_bool, parse_yaml_to_positive_integer, parse_yaml_to_string,
+    parse_yaml_to_string_or_vec, Result,
+};
+use crate::config::{
+    Auth, BasicAuth, BodyAssertion, HttpCheckerConfig, JsonAssertion, JsonOperator, OAuth2Config,
+    StatusRange,
+};
 use crate::error::ConfigError;
 
-pub fn parse(key: &Yaml, body: &Yaml) -> Result<CheckerConfig> {
-    let id = parse_key(key)?;
-    let mut notifiers: Vec<String> = vec![];
-    let mut basic_auth: Option<BasicAuth> = None;
+const DEFAULT_MAX_REDIRECTS: u32 = 10;
 
-    // Default interval is 10 sec
-    let mut interval = Duration::new(10, 0);
+pub fn parse(id: &str, attrs: &Hash) -> Result<HttpCheckerConfig> {
+    let mut auth: Option<Auth> = None;
     let mut url_opt: Option<Uri> = None;
+    let mut method = Method::GET;
+    let mut headers = HeaderMap::new();
+    let mut request_body: Option<String> = None;
 
-    match body {
-        Yaml::Hash(hash) => {
-            for (attr_yaml_key, attr_yaml_val) in hash {
-                let attr_key = parse_key(&attr_yaml_key)?;
+    // By default only `200 OK` is considered as a healthy response
+    let mut expected_status = vec![StatusRange { from: 200, to: 200 }];
+    let mut body_assertions: Vec<BodyAssertion> = vec![];
+    let mut json_assertions: Vec<JsonAssertion> = vec![];
 
-                match attr_key.as_ref() {
-                    "interval" => {
-                        let attr_val = parse_key(&attr_yaml_val)?;
-                        match attr_val.parse::<humantime::Duration>() {
-                            Ok(val) => {
-                                interval = val.into();
-                            }
-                            Err(_) => {
-                                let e = ConfigError::InvalidCheckerInterval {
-                                    interval: attr_val,
-                                    checker_id: id,
-                                };
-                                return Err(e);
-                            }
-                        }
+    // Redirects are not followed by default, so the redirect response itself is verified
+    let mut follow_redirects = false;
+    let mut max_redirects = DEFAULT_MAX_REDIRECTS;
+    let mut final_url: Option<Uri> = None;
+
+    for (attr_yaml_key, attr_yaml_val) in attrs {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        match attr_key.as_ref() {
+            "url" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                match attr_val.parse::<Uri>() {
+                    Ok(url) => {
+                        url_opt = Some(url);
                     }
-                    "url" => {
-                        let attr_val = parse_yaml_to_string(&attr_yaml_val)?;
-                        match attr_val.parse::<Uri>() {
-                            Ok(url) => {
-                                url_opt = Some(url);
-                            }
-                            Err(_) => {
-                                let e = ConfigError::InvalidCheckerUrl {
-                                    checker_id: id,
-                                    url: attr_val,
-                                };
-                                return Err(e);
-                            }
+                    Err(_) => {
+                        let e = ConfigError::InvalidCheckerUrl {
+                            checker_id: id.to_owned(),
+                            url: attr_val,
                         };
+                        return Err(e);
                     }
-                    "notifiers" => {
-                        notifiers = parse_yaml_to_vec(&attr_yaml_val)?;
-                    }
-                    "basic_auth" => {
-                        let raw_basic_auth = parse_basic_auth(&id, &attr_yaml_val)?;
-                        basic_auth = Some(raw_basic_auth);
+                };
+            }
+            "method" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                match Method::from_bytes(attr_val.to_uppercase().as_bytes()) {
+                    Ok(val) => {
+                        method = val;
                     }
-                    _ => {
-                        let err = ConfigError::UnknownCheckerAttribute {
-                            checker_id: id.clone(),
-                            attr_name: attr_key,
+                    Err(_) => {
+                        let e = ConfigError::InvalidCheckerMethod {
+                            checker_id: id.to_owned(),
+                            method: attr_val,
                         };
-                        return Err(err);
+                        return Err(e);
                     }
                 }
             }
+            "headers" => {
+                headers = parse_headers(id, attr_yaml_val)?;
+            }
+            "body" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                request_body = Some(attr_val);
+            }
+            "expected_status" => {
+                expected_status = parse_expected_status(id, attr_yaml_val)?;
+            }
+            "body_contains" => {
+                for text in parse_yaml_to_string_or_vec(attr_yaml_val)? {
+                    body_assertions.push(BodyAssertion::Contains(text));
+                }
+            }
+            "body_not_contains" => {
+                for text in parse_yaml_to_string_or_vec(attr_yaml_val)? {
+                    body_assertions.push(BodyAssertion::NotContains(text));
+                }
+            }
+            "body_regex" => {
+                for raw_regex in parse_yaml_to_string_or_vec(attr_yaml_val)? {
+                    match Regex::new(&raw_regex) {
+                        Ok(regex) => body_assertions.push(BodyAssertion::Regex(regex)),
+                        Err(_) => {
+                            let e = ConfigError::InvalidCheckerBodyRegex {
+                                checker_id: id.to_owned(),
+                                regex: raw_regex,
+                            };
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+            "json" => {
+                json_assertions = parse_json_assertions(id, attr_yaml_val)?;
+            }
+            "basic_auth" => {
+                let raw_basic_auth = parse_basic_auth(id, attr_yaml_val)?;
+                auth = Some(Auth::Basic(raw_basic_auth));
+            }
+            "auth" => {
+                auth = Some(parse_auth(id, attr_yaml_val)?);
+            }
+            "follow_redirects" => {
+                let path = format!("checkers.{}.follow_redirects", id);
+                follow_redirects = parse_yaml_to_bool(&path, attr_yaml_val)?;
+            }
+            "max_redirects" => {
+                let path = format!("checkers.{}.max_redirects", id);
+                max_redirects = parse_yaml_to_positive_integer(&path, attr_yaml_val)?;
+            }
+            "final_url" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                match attr_val.parse::<Uri>() {
+                    Ok(url) => {
+                        final_url = Some(url);
+                    }
+                    Err(_) => {
+                        let e = ConfigError::InvalidCheckerUrl {
+                            checker_id: id.to_owned(),
+                            url: attr_val,
+                        };
+                        return Err(e);
+                    }
+                };
+            }
+            _ => {
+                let err = ConfigError::UnknownCheckerAttribute {
+                    checker_id: id.to_owned(),
+                    attr_name: attr_key,
+                };
+                return Err(err);
+            }
         }
-        _ => {
-            let message = format!("`checkers.{}` must be a hash. Got {:?}", id, body);
-            return Err(ConfigError::GeneralError { message });
-        }
-    };
+    }
 
     let url = url_opt.ok_or(ConfigError::FieldMissing {
         path: format!("checkers.{}.url", id),
     })?;
 
-    let cf = CheckerConfig {
-        id,
-        interval,
+    let cf = HttpCheckerConfig {
         url,
-        basic_auth,
-        notifiers,
+        method,
+        headers,
+        body: request_body,
+        auth,
+        expected_status,
+        body_assertions,
+        json_assertions,
+        follow_redirects,
+        max_redirects,
+        final_url,
     };
     Ok(cf)
 }
 
+// JSON assertions are declared as an array of hashes with `path` and an operator, e.g.:
+//
+//   json:
+//     - path: /db
+//       equals: up
+//     - path: connections.active
+//       lt: 100
+fn parse_json_assertions(checker_id: &str, val: &Yaml) -> Result<Vec<JsonAssertion>> {
+    match val {
+        Yaml::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .map(|(index, item)| parse_json_assertion(checker_id, index, item))
+            .collect(),
+        _ => {
+            let message = format!(
+                "`checkers.{}.json` must be an array. Got {:?}",
+                checker_id, val
+            );
+            Err(ConfigError::GeneralError { message })
+        }
+    }
+}
+
+fn parse_json_assertion(checker_id: &str, index: usize, val: &Yaml) -> Result<JsonAssertion> {
+    let path = format!("checkers.{}.json[{}]", checker_id, index);
+    let hash = match val {
+        Yaml::Hash(hash) => hash,
+        _ => {
+            let message = format!("`{}` must be a hash. Got {:?}", path, val);
+            return Err(ConfigError::GeneralError { message });
+        }
+    };
+
+    let mut pointer_opt: Option<String> = None;
+    let mut operator_opt: Option<JsonOperator> = None;
+
+    for (attr_yaml_key, attr_yaml_val) in hash {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        let operator = match attr_key.as_ref() {
+            "path" => {
+                let raw_path = parse_yaml_to_string(attr_yaml_val)?;
+                pointer_opt = Some(path_to_json_pointer(&raw_path));
+                continue;
+            }
+            "equals" => JsonOperator::Equals(yaml_to_json(&path, attr_yaml_val)?),
+            "not_equals" => JsonOperator::NotEquals(yaml_to_json(&path, attr_yaml_val)?),
+            "gt" => JsonOperator::GreaterThan(yaml_to_number(&path, attr_yaml_val)?),
+            "gte" => JsonOperator::GreaterOrEqual(yaml_to_number(&path, attr_yaml_val)?),
+            "lt" => JsonOperator::LessThan(yaml_to_number(&path, attr_yaml_val)?),
+            "lte" => JsonOperator::LessOrEqual(yaml_to_number(&path, attr_yaml_val)?),
+            _ => {
+                let message = format!("Unknown attribute {}.{}", path, attr_key);
+                return Err(ConfigError::GeneralError { message });
+            }
+        };
+
+        if operator_opt.is_some() {
+            let message = format!("`{}` must have exactly one operator", path);
+            return Err(ConfigError::GeneralError { message });
+        }
+        operator_opt = Some(operator);
+    }
+
+    let pointer = pointer_opt.ok_or(ConfigError::FieldMissing {
+        path: format!("{}.path", path),
+    })?;
+    let operator = operator_opt.ok_or_else(|| {
+        let message = format!(
+            "`{}` must have one of the operators: equals, not_equals, gt, gte, lt, lte",
+            path
+        );
+        ConfigError::GeneralError { message }
+    })?;
+
+    Ok(JsonAssertion { pointer, operator })
+}
+
+// Path may be given either as a JSON pointer (`/db/status`) or as a dot separated path
+// (`db.status`), the latter one is converted into JSON pointer.
+fn path_to_json_pointer(path: &str) -> String {
+    if path.is_empty() || path.starts_with('/') {
+        return path.to_owned();
+    }
+
+    path.split('.')
+        .map(|token| format!("/{}", token.replace("~", "~0").replace("/", "~1")))
+        .collect()
+}
+
+fn yaml_to_json(path: &str, val: &Yaml) -> Result<JsonValue> {
+    let json = match val {
+        Yaml::String(s) => JsonValue::String(s.clone()),
+        Yaml::Integer(num) => JsonValue::from(*num),
+        Yaml::Real(_) => JsonValue::from(yaml_to_number(path, val)?),
+        Yaml::Boolean(b) => JsonValue::Bool(*b),
+        Yaml::Null => JsonValue::Null,
+        _ => {
+            let message = format!("`{}` must be compared with a scalar. Got {:?}", path, val);
+            return Err(ConfigError::GeneralError { message });
+        }
+    };
+    Ok(json)
+}
+
+fn yaml_to_number(path: &str, val: &Yaml) -> Result<f64> {
+    val.as_f64()
+        .or_else(|| val.as_i64().map(|num| num as f64))
+        .ok_or_else(|| {
+            let message = format!("`{}` must be compared with a number. Got {:?}", path, val);
+            ConfigError::GeneralError { message }
+        })
+}
+
+// Auth is a hash with `type` (basic, bearer, header or oauth2) and type specific attributes.
+fn parse_auth(checker_id: &str, val: &Yaml) -> Result<Auth> {
+    let path = format!("checkers.{}.auth", checker_id);
+    let hash = match val {
+        Yaml::Hash(hash) => hash,
+        _ => {
+            let message = format!("`{}` must be a hash. Got {:?}", path, val);
+            return Err(ConfigError::GeneralError { message });
+        }
+    };
+
+    let mut attrs: HashMap<String, String> = HashMap::new();
+    for (attr_yaml_key, attr_yaml_val) in hash {
+        let attr_key = parse_key(attr_yaml_key)?;
+        let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+        attrs.insert(attr_key, attr_val);
+    }
+
+    let type_value = attrs.remove("type").ok_or(ConfigError::FieldMissing {
+        path: format!("{}.type", path),
+    })?;
+    let allowed_attrs: &[&str] = match type_value.as_ref() {
+        "basic" => &["username", "password"],
+        "bearer" => &["token"],
+        "header" => &["name", "value"],
+        "oauth2" => &["token_url", "client_id", "client_secret", "scope"],
+        _ => {
+            let e = ConfigError::InvalidCheckerAuthType {
+                checker_id: checker_id.to_owned(),
+                type_value,
+            };
+            return Err(e);
+        }
+    };
+    if let Some(attr_key) = attrs.keys().find(|k| !allowed_attrs.contains(&k.as_str())) {
+        let message = format!("Unknown attribute {}.{}", path, attr_key);
+        return Err(ConfigError::GeneralError { message });
+    }
+
+    let mut take = |name: &str| {
+        attrs.remove(name).ok_or(ConfigError::FieldMissing {
+            path: format!("{}.{}", path, name),
+        })
+    };
+
+    let auth = match type_value.as_ref() {
+        "basic" => Auth::Basic(BasicAuth {
+            username: take("username")?,
+            password: take("password")?,
+        }),
+        "bearer" => {
+            let token = take("token")?;
+            let value = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|_| {
+                let message = format!("`{}.token` is not a valid header value", path);
+                ConfigError::GeneralError { message }
+            })?;
+            Auth::Bearer(value)
+        }
+        "header" => {
+            let name = take("name")?;
+            let value = take("value")?;
+            let header_name = HeaderName::from_bytes(name.as_bytes());
+            let header_value = HeaderValue::from_str(&value);
+            match (header_name, header_value) {
+                (Ok(header_name), Ok(header_value)) => Auth::Header(header_name, header_value),
+                _ => {
+                    let e = ConfigError::InvalidCheckerHeader {
+                        checker_id: checker_id.to_owned(),
+                        header: name,
+                    };
+                    return Err(e);
+                }
+            }
+        }
+        _ => {
+            let raw_token_url = take("token_url")?;
+            let token_url: Uri = raw_token_url.parse().map_err(|_| {
+                let message = format!(
+                    "`{}` in `{}.token_url` is not a valid URL",
+                    raw_token_url, path
+                );
+                ConfigError::GeneralError { message }
+            })?;
+            Auth::OAuth2(OAuth2Config {
+                token_url,
+                client_id: take("client_id")?,
+                client_secret: take("client_secret")?,
+                scope: take("scope").ok(),
+            })
+        }
+    };
+
+    Ok(auth)
+}
+
 fn parse_basic_auth(checker_id: &str, val: &Yaml) -> Result<BasicAuth> {
     match val {
         Yaml::Hash(hash) => {
@@ -96,15 +383,15 @@ fn parse_basic_auth(checker_id: &str, val: &Yaml) -> Result<BasicAuth> {
             let mut password_opt: Option<String> = None;
 
             for (attr_yaml_key, attr_yaml_val) in hash {
-                let attr_key = parse_key(&attr_yaml_key)?;
+                let attr_key = parse_key(attr_yaml_key)?;
 
                 match attr_key.as_ref() {
                     "username" => {
-                        let attr_val = parse_key(&attr_yaml_val)?;
+                        let attr_val = parse_key(attr_yaml_val)?;
                         username_opt = Some(attr_val);
                     }
                     "password" => {
-                        let attr_val = parse_key(&attr_yaml_val)?;
+                        let attr_val = parse_key(attr_yaml_val)?;
                         password_opt = Some(attr_val);
                     }
                     _ => {
@@ -134,3 +421,93 @@ fn parse_basic_auth(checker_id: &str, val: &Yaml) -> Result<BasicAuth> {
         }
     }
 }
+
+fn parse_headers(checker_id: &str, val: &Yaml) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+
+    match val {
+        Yaml::Hash(hash) => {
+            for (yaml_name, yaml_value) in hash {
+                let name = parse_key(yaml_name)?;
+                let value = parse_yaml_to_string(yaml_value)?;
+
+                let header_name = HeaderName::from_bytes(name.as_bytes());
+                let header_value = HeaderValue::from_str(&value);
+                match (header_name, header_value) {
+                    (Ok(header_name), Ok(header_value)) => {
+                        headers.append(header_name, header_value);
+                    }
+                    _ => {
+                        let e = ConfigError::InvalidCheckerHeader {
+                            checker_id: checker_id.to_owned(),
+                            header: name,
+                        };
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        _ => {
+            let message = format!(
+                "`checkers.{}.headers` must be a hash. Got {:?}",
+                checker_id, val
+            );
+            return Err(ConfigError::GeneralError { message });
+        }
+    }
+
+    Ok(headers)
+}
+
+// Expected status may be a single value or an array of values. Every value is either
+// a status code (`204`), a class of codes (`2xx`) or an inclusive range (`200-399`).
+fn parse_expected_status(checker_id: &str, val: &Yaml) -> Result<Vec<StatusRange>> {
+    let raw_values = parse_yaml_to_string_or_vec(val)?;
+
+    if raw_values.is_empty() {
+        let message = format!(
+            "`checkers.{}.expected_status` must not be empty",
+            checker_id
+        );
+        return Err(ConfigError::GeneralError { message });
+    }
+
+    raw_values
+        .into_iter()
+        .map(|raw| {
+            parse_status_range(&raw).ok_or_else(|| ConfigError::InvalidCheckerExpectedStatus {
+                checker_id: checker_id.to_owned(),
+                value: raw,
+            })
+        })
+        .collect()
+}
+
+fn parse_status_range(raw: &str) -> Option<StatusRange> {
+    let raw = raw.trim();
+
+    let range = if raw.len() == 3 && raw.ends_with("xx") {
+        let class: u16 = raw[..1].parse().ok()?;
+        StatusRange {
+            from: class * 100,
+            to: class * 100 + 99,
+        }
+    } else if let Some(pos) = raw.find('-') {
+        let from: u16 = raw[..pos].trim().parse().ok()?;
+        let to: u16 = raw[pos + 1..].trim().parse().ok()?;
+        StatusRange { from, to }
+    } else {
+        let code: u16 = raw.parse().ok()?;
+        StatusRange {
+            from: code,
+            to: code,
+        }
+    };
+
+    let is_valid = 100 <= range.from && range.from <= range.to && range.to <= 599;
+    if is_valid {
+        Some(range)
+    } else {
+        None
+    }
+}
@@ -1,28 +1,310 @@
-use yaml_rust::yaml::Yaml;
+use yaml_rust::yaml::{Hash, Yaml};
 
-use crate::config::CheckerConfig;
+use std::time::Duration;
+
+use crate::config::{CheckerConfig, CheckerKind, EscalationStep, Transition};
 use crate::error::ConfigError;
+use crate::reactor::State;
 
 use super::common;
-use super::common::Result;
+use super::common::{
+    parse_key, parse_yaml_to_positive_integer, parse_yaml_to_string, parse_yaml_to_string_or_vec,
+    parse_yaml_to_vec, Result,
+};
+use super::defaults::Defaults;
 
+mod command;
+mod dns;
+mod heartbeat;
 mod http;
+mod tcp;
+mod tls;
 
-pub fn parse_checkers(checker_configs: &Yaml) -> Result<Vec<CheckerConfig>> {
+pub fn parse_checkers(checker_configs: &Yaml, defaults: &Defaults) -> Result<Vec<CheckerConfig>> {
     let mut checkers = vec![];
 
     match checker_configs {
         Yaml::Hash(hash) => {
             for (yaml_key, val) in hash.iter() {
-                let checker = http::parse(yaml_key, val)?;
+                let checker = parse_checker(yaml_key, val, defaults)?;
                 checkers.push(checker);
             }
         }
         _ => {
-            let message = format!("`checkers` must be a hash. Got {:?}", checker_configs);
-            return Err(ConfigError::GeneralError { message });
+            let e = ConfigError::InvalidCheckers {
+                value: format!("{:?}", checker_configs),
+            };
+            return Err(e);
         }
     }
 
     Ok(checkers)
 }
+
+// Parses attributes, that are common for all kinds of checkers. The rest of the attributes
+// is parsed according to `type` (default: `http`).
+fn parse_checker(key: &Yaml, body: &Yaml, defaults: &Defaults) -> Result<CheckerConfig> {
+    let id = parse_key(key)?;
+    let mut checker_type = "http".to_owned();
+    let mut notifiers: Vec<String> = vec![];
+
+    // Default interval is 10 sec
+    let mut interval = Duration::new(10, 0);
+    // Default timeout is 10 sec
+    let mut timeout = Duration::new(10, 0);
+    let mut fail_threshold = defaults.fail_threshold;
+    let mut success_threshold = defaults.success_threshold;
+    let mut tags: Vec<String> = vec![];
+    let mut warn_latency: Option<Duration> = None;
+    let mut max_latency: Option<Duration> = None;
+    let mut notify_on = Transition::all();
+    let mut remind_every: Option<Duration> = None;
+    let mut escalation: Vec<EscalationStep> = vec![];
+    let mut depends_on: Vec<String> = vec![];
+
+    // Attributes specific for the kind of the checker
+    let mut kind_attrs = Hash::new();
+
+    match body {
+        Yaml::Hash(hash) => {
+            for (attr_yaml_key, attr_yaml_val) in hash {
+                let attr_key = parse_key(attr_yaml_key)?;
+
+                match attr_key.as_ref() {
+                    "type" => {
+                        checker_type = parse_yaml_to_string(attr_yaml_val)?;
+                    }
+                    "interval" => {
+                        let attr_val = parse_key(attr_yaml_val)?;
+                        match attr_val.parse::<humantime::Duration>() {
+                            Ok(val) => {
+                                interval = val.into();
+                            }
+                            Err(_) => {
+                                let e = ConfigError::InvalidCheckerInterval {
+                                    interval: attr_val,
+                                    checker_id: id,
+                                };
+                                return Err(e);
+                            }
+                        }
+                    }
+                    "timeout" => {
+                        let attr_val = parse_key(attr_yaml_val)?;
+                        match attr_val.parse::<humantime::Duration>() {
+                            Ok(val) => {
+                                timeout = val.into();
+                            }
+                            Err(_) => {
+                                let e = ConfigError::InvalidCheckerTimeout {
+                                    timeout: attr_val,
+                                    checker_id: id,
+                                };
+                                return Err(e);
+                            }
+                        }
+                    }
+                    "notifiers" => {
+                        notifiers = parse_yaml_to_vec(attr_yaml_val)?;
+                    }
+                    "fail_threshold" => {
+                        let path = format!("checkers.{}.fail_threshold", id);
+                        fail_threshold = parse_yaml_to_positive_integer(&path, attr_yaml_val)?;
+                    }
+                    "success_threshold" => {
+                        let path = format!("checkers.{}.success_threshold", id);
+                        success_threshold = parse_yaml_to_positive_integer(&path, attr_yaml_val)?;
+                    }
+                    "tags" => {
+                        tags = parse_yaml_to_string_or_vec(attr_yaml_val)?;
+                    }
+                    "warn_latency" => {
+                        warn_latency = Some(parse_duration(&id, &attr_key, attr_yaml_val)?);
+                    }
+                    "max_latency" => {
+                        max_latency = Some(parse_duration(&id, &attr_key, attr_yaml_val)?);
+                    }
+                    "notify_on" => {
+                        notify_on = parse_transitions(&id, attr_yaml_val)?;
+                    }
+                    "remind_every" => {
+                        remind_every = Some(parse_duration(&id, &attr_key, attr_yaml_val)?);
+                    }
+                    "escalation" => {
+                        escalation = parse_escalation(&id, attr_yaml_val)?;
+                    }
+                    "depends_on" => {
+                        depends_on = parse_yaml_to_string_or_vec(attr_yaml_val)?;
+                    }
+                    _ => {
+                        kind_attrs.insert(attr_yaml_key.clone(), attr_yaml_val.clone());
+                    }
+                }
+            }
+        }
+        _ => {
+            let e = ConfigError::InvalidChecker {
+                checker_id: id,
+                value: format!("{:?}", body),
+            };
+            return Err(e);
+        }
+    };
+
+    if let (Some(warn), Some(max)) = (warn_latency, max_latency) {
+        if warn >= max {
+            return Err(ConfigError::InvalidCheckerLatency { checker_id: id });
+        }
+    }
+
+    let kind = match checker_type.as_ref() {
+        "http" => CheckerKind::Http(http::parse(&id, &kind_attrs)?),
+        "tcp" => CheckerKind::Tcp(tcp::parse(&id, &kind_attrs)?),
+        "dns" => CheckerKind::Dns(dns::parse(&id, &kind_attrs)?),
+        "tls" => CheckerKind::Tls(tls::parse(&id, &kind_attrs)?),
+        "command" => CheckerKind::Command(command::parse(&id, &kind_attrs)?),
+        "heartbeat" => CheckerKind::Heartbeat(heartbeat::parse(&id, &kind_attrs)?),
+        _ => {
+            let e = ConfigError::InvalidCheckerType {
+                checker_id: id,
+                type_value: checker_type,
+            };
+            return Err(e);
+        }
+    };
+
+    let cf = CheckerConfig {
+        id,
+        kind,
+        interval,
+        timeout,
+        notifiers,
+        fail_threshold,
+        success_threshold,
+        tags,
+        warn_latency,
+        max_latency,
+        notify_on,
+        remind_every,
+        escalation,
+        depends_on,
+    };
+    Ok(cf)
+}
+
+pub(super) fn parse_duration(id: &str, attr_name: &str, val: &Yaml) -> Result<Duration> {
+    let attr_val = parse_key(val)?;
+    match attr_val.parse::<humantime::Duration>() {
+        Ok(val) => Ok(val.into()),
+        Err(_) => {
+            let e = ConfigError::InvalidCheckerDuration {
+                checker_id: id.to_owned(),
+                attr_name: attr_name.to_owned(),
+                value: attr_val,
+            };
+            Err(e)
+        }
+    }
+}
+
+// Escalation is declared as an array of steps, e.g.:
+//
+//   escalation:
+//     - after: 10m
+//       notifiers: [oncall_telegram]
+//     - after: 30m
+//       notifiers: [pager]
+fn parse_escalation(checker_id: &str, val: &Yaml) -> Result<Vec<EscalationStep>> {
+    let mut steps = vec![];
+
+    let items = match val {
+        Yaml::Array(items) => items,
+        _ => {
+            let e = ConfigError::InvalidCheckerEscalation {
+                checker_id: checker_id.to_owned(),
+                value: format!("{:?}", val),
+            };
+            return Err(e);
+        }
+    };
+
+    for item in items.iter() {
+        let hash = common::parse_yaml_to_hash(item)?;
+        let mut after: Option<Duration> = None;
+        let mut notifiers: Option<Vec<String>> = None;
+
+        for (attr_yaml_key, attr_yaml_val) in hash {
+            let attr_key = parse_key(attr_yaml_key)?;
+            match attr_key.as_ref() {
+                "after" => {
+                    after = Some(parse_duration(
+                        checker_id,
+                        "escalation.after",
+                        attr_yaml_val,
+                    )?);
+                }
+                "notifiers" => {
+                    notifiers = Some(parse_yaml_to_vec(attr_yaml_val)?);
+                }
+                _ => {
+                    let err = ConfigError::UnknownCheckerAttribute {
+                        checker_id: checker_id.to_owned(),
+                        attr_name: format!("escalation.{}", attr_key),
+                    };
+                    return Err(err);
+                }
+            }
+        }
+
+        let after = after.ok_or(ConfigError::FieldMissing {
+            path: format!("checkers.{}.escalation.after", checker_id),
+        })?;
+        let notifiers = notifiers.ok_or(ConfigError::FieldMissing {
+            path: format!("checkers.{}.escalation.notifiers", checker_id),
+        })?;
+        steps.push(EscalationStep { after, notifiers });
+    }
+
+    Ok(steps)
+}
+
+// Transitions are declared as a state (e.g. `down`) or as a pair of states (e.g. `degraded->up`).
+fn parse_transitions(checker_id: &str, val: &Yaml) -> Result<Vec<Transition>> {
+    let mut transitions = vec![];
+
+    for raw_value in parse_yaml_to_string_or_vec(val)? {
+        let states: Vec<Option<State>> = raw_value.split("->").map(parse_state).collect();
+        let transition = match states.as_slice() {
+            [Some(to)] => Some(Transition {
+                from: None,
+                to: to.clone(),
+            }),
+            [Some(from), Some(to)] if from != to => Some(Transition {
+                from: Some(from.clone()),
+                to: to.clone(),
+            }),
+            _ => None,
+        };
+        match transition {
+            Some(transition) => transitions.push(transition),
+            None => {
+                let e = ConfigError::InvalidCheckerTransition {
+                    checker_id: checker_id.to_owned(),
+                    value: raw_value,
+                };
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(transitions)
+}
+
+fn parse_state(name: &str) -> Option<State> {
+    match name.trim() {
+        "up" => Some(State::Up),
+        "degraded" => Some(State::Degraded),
+        "down" => Some(State::Down),
+        _ => None,
+    }
+}
@@ -0,0 +1,53 @@
+use yaml_rust::yaml::Hash;
+
+use super::common::{parse_key, parse_yaml_to_string, Result};
+use crate::config::TcpCheckerConfig;
+use crate::error::ConfigError;
+
+pub fn parse(id: &str, attrs: &Hash) -> Result<TcpCheckerConfig> {
+    let mut address: Option<(String, u16)> = None;
+
+    for (attr_yaml_key, attr_yaml_val) in attrs {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        match attr_key.as_ref() {
+            "address" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                match parse_address(&attr_val) {
+                    Some(host_port) => address = Some(host_port),
+                    None => {
+                        let e = ConfigError::InvalidCheckerAddress {
+                            checker_id: id.to_owned(),
+                            address: attr_val,
+                        };
+                        return Err(e);
+                    }
+                }
+            }
+            _ => {
+                let err = ConfigError::UnknownCheckerAttribute {
+                    checker_id: id.to_owned(),
+                    attr_name: attr_key,
+                };
+                return Err(err);
+            }
+        }
+    }
+
+    let (host, port) = address.ok_or(ConfigError::FieldMissing {
+        path: format!("checkers.{}.address", id),
+    })?;
+    Ok(TcpCheckerConfig { host, port })
+}
+
+// Splits `host:port` into parts. IPv6 addresses must be enclosed in brackets, e.g. `[::1]:5432`.
+pub fn parse_address(address: &str) -> Option<(String, u16)> {
+    let pos = address.rfind(':')?;
+    let (host, port) = (&address[..pos], &address[pos + 1..]);
+    let port = port.parse::<u16>().ok()?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_owned(), port))
+}
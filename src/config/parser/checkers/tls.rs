@@ -0,0 +1,70 @@
+use yaml_rust::yaml::Hash;
+
+use std::time::Duration;
+
+use super::common::{parse_key, parse_yaml_to_string, Result};
+use super::parse_duration;
+use super::tcp::parse_address;
+use crate::config::TlsCheckerConfig;
+use crate::error::ConfigError;
+
+const HTTPS_PORT: u16 = 443;
+
+// Default is 14 days
+const DEFAULT_EXPIRY_WINDOW: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+pub fn parse(id: &str, attrs: &Hash) -> Result<TlsCheckerConfig> {
+    let mut address: Option<(String, u16)> = None;
+    let mut server_name: Option<String> = None;
+    let mut expiry_window = DEFAULT_EXPIRY_WINDOW;
+
+    for (attr_yaml_key, attr_yaml_val) in attrs {
+        let attr_key = parse_key(attr_yaml_key)?;
+
+        match attr_key.as_ref() {
+            "address" => {
+                let attr_val = parse_yaml_to_string(attr_yaml_val)?;
+                // Port may be omitted, e.g. `example.com`
+                let host_port = if attr_val.contains(':') {
+                    parse_address(&attr_val)
+                } else {
+                    Some((attr_val.clone(), HTTPS_PORT))
+                };
+                match host_port {
+                    Some(host_port) => address = Some(host_port),
+                    None => {
+                        let e = ConfigError::InvalidCheckerAddress {
+                            checker_id: id.to_owned(),
+                            address: attr_val,
+                        };
+                        return Err(e);
+                    }
+                }
+            }
+            "server_name" => {
+                server_name = Some(parse_yaml_to_string(attr_yaml_val)?);
+            }
+            "expiry_window" => {
+                expiry_window = parse_duration(id, &attr_key, attr_yaml_val)?;
+            }
+            _ => {
+                let err = ConfigError::UnknownCheckerAttribute {
+                    checker_id: id.to_owned(),
+                    attr_name: attr_key,
+                };
+                return Err(err);
+            }
+        }
+    }
+
+    let (host, port) = address.ok_or(ConfigError::FieldMissing {
+        path: format!("checkers.{}.address", id),
+    })?;
+
+    Ok(TlsCheckerConfig {
+        host,
+        port,
+        server_name,
+        expiry_window,
+    })
+}
@@ -1,5 +1,7 @@
 use yaml_rust::yaml::Yaml;
 
+use std::net::SocketAddr;
+
 use crate::error::ConfigError;
 
 pub type Result<T> = std::result::Result<T, ConfigError>;
@@ -26,6 +28,37 @@ pub fn parse_yaml_to_string(val: &Yaml) -> Result<String> {
     }
 }
 
+pub fn parse_yaml_to_positive_integer(path: &str, val: &Yaml) -> Result<u32> {
+    match val {
+        Yaml::Integer(num) if *num > 0 && *num <= i64::from(u32::MAX) => Ok(*num as u32),
+        _ => {
+            let message = format!("`{}` must be a positive integer. Got {:?}", path, val);
+            Err(ConfigError::GeneralError { message })
+        }
+    }
+}
+
+pub fn parse_yaml_to_bool(path: &str, val: &Yaml) -> Result<bool> {
+    match val {
+        Yaml::Boolean(b) => Ok(*b),
+        _ => {
+            let message = format!("`{}` must be a boolean. Got {:?}", path, val);
+            Err(ConfigError::GeneralError { message })
+        }
+    }
+}
+
+pub fn parse_yaml_to_socket_addr(path: &str, val: &Yaml) -> Result<SocketAddr> {
+    let addr = parse_yaml_to_string(val)?;
+    addr.parse().map_err(|_| {
+        let message = format!(
+            "`{}` must be an address like `127.0.0.1:9090`. Got `{}`",
+            path, addr
+        );
+        ConfigError::GeneralError { message }
+    })
+}
+
 pub fn parse_yaml_to_vec(val: &Yaml) -> Result<Vec<String>> {
     let mut items: Vec<String> = vec![];
 
@@ -45,6 +78,14 @@ pub fn parse_yaml_to_vec(val: &Yaml) -> Result<Vec<String>> {
     Ok(items)
 }
 
+// Accepts either a single value or an array of values.
+pub fn parse_yaml_to_string_or_vec(val: &Yaml) -> Result<Vec<String>> {
+    match val {
+        Yaml::Array(_) => parse_yaml_to_vec(val),
+        _ => Ok(vec![parse_yaml_to_string(val)?]),
+    }
+}
+
 pub fn parse_yaml_to_hash(val: &Yaml) -> Result<&yaml_rust::yaml::Hash> {
     match val {
         Yaml::Hash(hash) => Ok(hash),
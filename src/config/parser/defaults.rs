@@ -0,0 +1,54 @@
+use yaml_rust::yaml::Yaml;
+
+use super::common::{parse_key, parse_yaml_to_positive_integer, Result};
+use crate::error::ConfigError;
+
+// Default values for checker attributes, declared in the root `defaults` section.
+#[derive(Debug, Clone)]
+pub struct Defaults {
+    pub fail_threshold: u32,
+    pub success_threshold: u32,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            fail_threshold: 1,
+            success_threshold: 1,
+        }
+    }
+}
+
+pub fn parse_defaults(body: &Yaml) -> Result<Defaults> {
+    let mut defaults = Defaults::default();
+
+    match body {
+        Yaml::Hash(hash) => {
+            for (attr_yaml_key, attr_yaml_val) in hash {
+                let attr_key = parse_key(attr_yaml_key)?;
+                let path = format!("defaults.{}", attr_key);
+
+                match attr_key.as_ref() {
+                    "fail_threshold" => {
+                        defaults.fail_threshold =
+                            parse_yaml_to_positive_integer(&path, attr_yaml_val)?;
+                    }
+                    "success_threshold" => {
+                        defaults.success_threshold =
+                            parse_yaml_to_positive_integer(&path, attr_yaml_val)?;
+                    }
+                    _ => {
+                        let message = format!("Unknown attribute {}", path);
+                        return Err(ConfigError::GeneralError { message });
+                    }
+                }
+            }
+        }
+        _ => {
+            let message = format!("`defaults` must be a hash. Got {:?}", body);
+            return Err(ConfigError::GeneralError { message });
+        }
+    }
+
+    Ok(defaults)
+}
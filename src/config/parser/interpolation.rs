@@ -0,0 +1,146 @@
+// Resolves references to the environment and secret files, so credentials do not have to be
+// stored in the config file:
+//
+// * `${VAR}` in any string value is replaced with value of environment variable `VAR`,
+//   `${VAR:-default}` falls back to `default` if the variable is not set, `$${` escapes `${`.
+//   Values of `command` are left as is, because they are often shell scripts with variables.
+// * `<name>_file: /path/to/secret` is replaced with `<name>: <content of the file>`,
+//   if `<name>` is one of SECRET_KEYS or a header.
+
+use yaml_rust::yaml::{Hash, Yaml};
+
+use std::env;
+use std::fs;
+
+use super::common::{parse_key, Result};
+use crate::error::ConfigError;
+
+const FILE_SUFFIX: &str = "_file";
+
+// Attributes, that may hold credentials or URLs with credentials.
+const SECRET_KEYS: &[&str] = &[
+    "url",
+    "base_url",
+    "webhook_url",
+    "token_url",
+    "username",
+    "password",
+    "token",
+    "value",
+    "client_id",
+    "client_secret",
+    "chat_id",
+    "room_id",
+    "admin_token",
+];
+
+// Arguments of commands are not interpolated.
+const RAW_KEYS: &[&str] = &["command"];
+
+pub fn interpolate(doc: &Yaml) -> Result<Yaml> {
+    interpolate_value(doc, "")
+}
+
+fn interpolate_value(val: &Yaml, path: &str) -> Result<Yaml> {
+    let resolved = match val {
+        Yaml::String(s) => Yaml::String(interpolate_string(s, path)?),
+        Yaml::Array(arr) => {
+            let mut items = vec![];
+            for (index, item) in arr.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, index);
+                items.push(interpolate_value(item, &item_path)?);
+            }
+            Yaml::Array(items)
+        }
+        Yaml::Hash(hash) => Yaml::Hash(interpolate_hash(hash, path)?),
+        _ => val.clone(),
+    };
+    Ok(resolved)
+}
+
+fn interpolate_hash(hash: &Hash, path: &str) -> Result<Hash> {
+    let mut resolved = Hash::new();
+
+    for (yaml_key, val) in hash.iter() {
+        let key = parse_key(yaml_key)?;
+        let key_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+
+        if RAW_KEYS.contains(&key.as_str()) {
+            resolved.insert(yaml_key.clone(), val.clone());
+            continue;
+        }
+
+        if let (Some(name), Yaml::String(file_path)) = (key.strip_suffix(FILE_SUFFIX), val) {
+            let is_header = path.ends_with(".headers");
+            if !name.is_empty() && (is_header || SECRET_KEYS.contains(&name)) {
+                let file_path = interpolate_string(file_path, &key_path)?;
+                let content = read_secret_file(&file_path, &key_path)?;
+                resolved.insert(Yaml::String(name.to_owned()), Yaml::String(content));
+                continue;
+            }
+        }
+
+        resolved.insert(yaml_key.clone(), interpolate_value(val, &key_path)?);
+    }
+
+    Ok(resolved)
+}
+
+fn interpolate_string(s: &str, path: &str) -> Result<String> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if rest.starts_with("$${") {
+            result.push_str("${");
+            rest = &rest[3..];
+        } else if rest.starts_with("${") {
+            let end = rest.find('}').ok_or_else(|| {
+                let message = format!("Unclosed `${{` in `{}`", path);
+                ConfigError::GeneralError { message }
+            })?;
+            result.push_str(&resolve_variable(&rest[2..end], path)?);
+            rest = &rest[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn resolve_variable(expr: &str, path: &str) -> Result<String> {
+    let (name, default) = match expr.find(":-") {
+        Some(pos) => (&expr[..pos], Some(&expr[pos + 2..])),
+        None => (expr, None),
+    };
+
+    match (env::var(name), default) {
+        (Ok(value), _) => Ok(value),
+        (Err(_), Some(default)) => Ok(default.to_owned()),
+        (Err(_), None) => Err(ConfigError::EnvVarMissing {
+            name: name.to_owned(),
+            path: path.to_owned(),
+        }),
+    }
+}
+
+fn read_secret_file(file_path: &str, path: &str) -> Result<String> {
+    let content = fs::read_to_string(file_path).map_err(|err| ConfigError::SecretFileError {
+        file: file_path.to_owned(),
+        path: path.to_owned(),
+        message: err.to_string(),
+    })?;
+
+    // Files usually end with a newline, that is not part of the secret
+    Ok(content.trim().to_owned())
+}
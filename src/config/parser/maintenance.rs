@@ -0,0 +1,131 @@
+use yaml_rust::yaml::Yaml;
+
+use std::time::{Duration, SystemTime};
+
+use super::common::{parse_key, parse_yaml_to_bool, parse_yaml_to_string_or_vec, Result};
+use crate::config::{MaintenanceSchedule, MaintenanceScope, MaintenanceWindow};
+use crate::error::ConfigError;
+use crate::maintenance::Cron;
+
+// Maintenance windows are declared as a hash, e.g.:
+//
+//   maintenance:
+//     nightly_backup:
+//       schedule: "0 3 * * *"
+//       duration: 30m
+//       tags: [db]
+//     migration:
+//       start: 2019-03-01T22:00:00Z
+//       end: 2019-03-02T02:00:00Z
+pub fn parse_maintenance(body: &Yaml) -> Result<Vec<MaintenanceWindow>> {
+    let mut windows = vec![];
+
+    match body {
+        Yaml::Hash(hash) => {
+            for (yaml_key, val) in hash.iter() {
+                let id = parse_key(yaml_key)?;
+                windows.push(parse_window(id, val)?);
+            }
+        }
+        _ => {
+            let message = format!("`maintenance` must be a hash. Got {:?}", body);
+            return Err(ConfigError::GeneralError { message });
+        }
+    }
+
+    Ok(windows)
+}
+
+fn parse_window(id: String, body: &Yaml) -> Result<MaintenanceWindow> {
+    let mut cron: Option<Cron> = None;
+    let mut duration: Option<Duration> = None;
+    let mut start: Option<SystemTime> = None;
+    let mut end: Option<SystemTime> = None;
+    let mut scope = MaintenanceScope::default();
+    let mut summary = true;
+
+    let hash = match body {
+        Yaml::Hash(hash) => hash,
+        _ => {
+            let message = format!("`maintenance.{}` must be a hash. Got {:?}", id, body);
+            return Err(ConfigError::GeneralError { message });
+        }
+    };
+
+    for (attr_yaml_key, attr_yaml_val) in hash {
+        let attr_key = parse_key(attr_yaml_key)?;
+        let path = format!("maintenance.{}.{}", id, attr_key);
+
+        match attr_key.as_ref() {
+            "schedule" => {
+                let expression = parse_key(attr_yaml_val)?;
+                let parsed = Cron::parse(&expression).map_err(|err| {
+                    let message = format!("`{}` is not a valid cron expression: {}", path, err);
+                    ConfigError::GeneralError { message }
+                })?;
+                cron = Some(parsed);
+            }
+            "duration" => {
+                let value = parse_key(attr_yaml_val)?;
+                let parsed = value.parse::<humantime::Duration>().map_err(|_| {
+                    let message = format!("`{}` is not a valid duration: {}", path, value);
+                    ConfigError::GeneralError { message }
+                })?;
+                duration = Some(parsed.into());
+            }
+            "start" => {
+                start = Some(parse_time(&path, attr_yaml_val)?);
+            }
+            "end" => {
+                end = Some(parse_time(&path, attr_yaml_val)?);
+            }
+            "checkers" => {
+                scope.checkers = parse_yaml_to_string_or_vec(attr_yaml_val)?;
+            }
+            "tags" => {
+                scope.tags = parse_yaml_to_string_or_vec(attr_yaml_val)?;
+            }
+            "summary" => {
+                summary = parse_yaml_to_bool(&path, attr_yaml_val)?;
+            }
+            _ => {
+                let message = format!("Unknown attribute {}", path);
+                return Err(ConfigError::GeneralError { message });
+            }
+        }
+    }
+
+    let schedule = match (cron, duration, start, end) {
+        (Some(cron), Some(duration), None, None) => MaintenanceSchedule::Cron { cron, duration },
+        (None, None, Some(start), Some(end)) => {
+            if start >= end {
+                let message = format!("`maintenance.{}.start` must be before `end`", id);
+                return Err(ConfigError::GeneralError { message });
+            }
+            MaintenanceSchedule::Range { start, end }
+        }
+        _ => {
+            let message = format!(
+                "`maintenance.{}` must have either `schedule` and `duration` or `start` and `end`",
+                id
+            );
+            return Err(ConfigError::GeneralError { message });
+        }
+    };
+
+    Ok(MaintenanceWindow {
+        id,
+        schedule,
+        scope,
+        summary,
+    })
+}
+
+// Time is expected in RFC 3339 format, e.g. `2019-03-01T22:00:00Z`.
+fn parse_time(path: &str, val: &Yaml) -> Result<SystemTime> {
+    let value = parse_key(val)?;
+    humantime::parse_rfc3339_weak(&value).map_err(|_| {
+        let message = format!("`{}` is not a valid time: {}", path, value);
+        ConfigError::GeneralError { message }
+    })
+}
@@ -0,0 +1,52 @@
+use yaml_rust::yaml::Yaml;
+
+use std::net::SocketAddr;
+
+use super::common::{parse_key, parse_yaml_to_socket_addr, parse_yaml_to_string, Result};
+use crate::config::MetricsConfig;
+use crate::error::ConfigError;
+
+const DEFAULT_PATH: &str = "/metrics";
+
+pub fn parse_metrics(body: &Yaml) -> Result<MetricsConfig> {
+    let mut listen: Option<SocketAddr> = None;
+    let mut path = DEFAULT_PATH.to_owned();
+
+    match body {
+        Yaml::Hash(hash) => {
+            for (attr_yaml_key, attr_yaml_val) in hash {
+                let attr_key = parse_key(attr_yaml_key)?;
+
+                match attr_key.as_ref() {
+                    "listen" => {
+                        listen = Some(parse_yaml_to_socket_addr("metrics.listen", attr_yaml_val)?);
+                    }
+                    "path" => {
+                        path = parse_yaml_to_string(attr_yaml_val)?;
+                        if !path.starts_with('/') {
+                            let message =
+                                format!("`metrics.path` must start with `/`. Got `{}`", path);
+                            return Err(ConfigError::GeneralError { message });
+                        }
+                    }
+                    _ => {
+                        let message = format!("Unknown attribute metrics.{}", attr_key);
+                        return Err(ConfigError::GeneralError { message });
+                    }
+                }
+            }
+        }
+        _ => {
+            let message = format!("`metrics` must be a hash. Got {:?}", body);
+            return Err(ConfigError::GeneralError { message });
+        }
+    }
+
+    match listen {
+        Some(listen) => Ok(MetricsConfig { listen, path }),
+        None => {
+            let message = "`metrics.listen` is missing".to_owned();
+            Err(ConfigError::GeneralError { message })
+        }
+    }
+}
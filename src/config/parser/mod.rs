@@ -5,29 +5,56 @@ use crate::error::ConfigError;
 
 mod checkers;
 mod common;
+mod defaults;
+mod interpolation;
+mod maintenance;
+mod metrics;
 mod notifiers;
+mod server;
 
 use self::common::{parse_key, Result};
+use self::defaults::{parse_defaults, Defaults};
 
 pub fn parse_config(yaml: &str) -> Result<FileConfig> {
     let mut checkers = vec![];
     let mut notifiers = vec![];
+    let mut metrics = None;
+    let mut server = None;
+    let mut maintenance = vec![];
 
     let docs = YamlLoader::load_from_str(yaml).map_err(|err| ConfigError::InvalidYaml { err })?;
 
-    for doc in docs.iter() {
+    for raw_doc in docs.iter() {
+        let doc = interpolation::interpolate(raw_doc)?;
+
         match doc {
             Yaml::Hash(root) => {
+                // Defaults must be known before checkers are parsed
+                let defaults = match root.get(&Yaml::String("defaults".to_owned())) {
+                    Some(val) => parse_defaults(val)?,
+                    None => Defaults::default(),
+                };
+
                 for (yaml_key, val) in root.iter() {
                     let key = parse_key(yaml_key)?;
 
                     match key.as_ref() {
+                        "defaults" => (),
                         "checkers" => {
-                            checkers = checkers::parse_checkers(val)?;
+                            checkers = checkers::parse_checkers(val, &defaults)?;
                         }
                         "notifiers" => {
                             notifiers = notifiers::parse_notifiers(val)?;
                         }
+                        "metrics" => {
+                            metrics = Some(metrics::parse_metrics(val)?);
+                        }
+                        "server" => {
+                            server = Some(server::parse_server(val)?);
+                        }
+                        "maintenance" => {
+                            maintenance = maintenance::parse_maintenance(val)?;
+                        }
                         _ => {
                             return Err(ConfigError::UnkownRootElement { name: key });
                         }
@@ -45,12 +72,32 @@ pub fn parse_config(yaml: &str) -> Result<FileConfig> {
     Ok(FileConfig {
         checkers,
         notifiers,
+        metrics,
+        server,
+        maintenance,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::validator::validate_config;
+    use crate::config::{
+        CheckerConfig, CheckerKind, CommandCheckerConfig, EscalationStep, HeartbeatCheckerConfig,
+        HttpCheckerConfig, MaintenanceSchedule, MaintenanceScope, MaintenanceWindow, MetricsConfig,
+        NotifierConfig, StatusRange, TcpCheckerConfig, TlsCheckerConfig,
+    };
+    use crate::error::ConfigValidationError;
+    use crate::maintenance::Cron;
+    use crate::reactor::State;
+    use std::time::Duration;
+
+    fn http_config(checker: &CheckerConfig) -> &HttpCheckerConfig {
+        match checker.kind {
+            CheckerKind::Http(ref http) => http,
+            _ => panic!("Expected HTTP checker"),
+        }
+    }
 
     #[test]
     fn test_valid_yaml() {
@@ -117,4 +164,751 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_checker_expected_status() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/
+                expected_status: [204, 3xx, 400-403]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        let ranges = &http_config(&config.checkers[0]).expected_status;
+        assert_eq!(
+            ranges,
+            &vec![
+                StatusRange { from: 204, to: 204 },
+                StatusRange { from: 300, to: 399 },
+                StatusRange { from: 400, to: 403 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checker_invalid_expected_status() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/
+                expected_status: 399-200
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerExpectedStatus {
+                checker_id: "greyblake".to_owned(),
+                value: "399-200".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_invalid_body_regex() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/
+                body_regex: "status: (ok"
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerBodyRegex {
+                checker_id: "greyblake".to_owned(),
+                regex: "status: (ok".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_json_assertions() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/health
+                json:
+                  - path: /db
+                    equals: up
+                  - path: connections.active
+                    lt: 100
+        "#;
+        let config = parse_config(yaml).unwrap();
+        let pointers: Vec<&str> = http_config(&config.checkers[0])
+            .json_assertions
+            .iter()
+            .map(|a| a.pointer.as_ref())
+            .collect();
+        assert_eq!(pointers, vec!["/db", "/connections/active"]);
+    }
+
+    #[test]
+    fn test_checker_json_assertion_without_path() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/health
+                json:
+                  - equals: up
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::FieldMissing {
+                path: "checkers.greyblake.json[0].path".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_thresholds() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/
+                success_threshold: 2
+              example:
+                url: https://www.example.com/
+
+            defaults:
+              fail_threshold: 3
+        "#;
+        let config = parse_config(yaml).unwrap();
+        let greyblake = config.get_checker_by_id("greyblake").unwrap();
+        assert_eq!(greyblake.fail_threshold, 3);
+        assert_eq!(greyblake.success_threshold, 2);
+        let example = config.get_checker_by_id("example").unwrap();
+        assert_eq!(example.fail_threshold, 3);
+        assert_eq!(example.success_threshold, 1);
+    }
+
+    #[test]
+    fn test_checker_request_attributes() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/ping
+                method: post
+                headers:
+                  X-Api-Key: secret
+                body: '{"ping": true}'
+        "#;
+        let config = parse_config(yaml).unwrap();
+        let checker = http_config(&config.checkers[0]);
+        assert_eq!(checker.method, hyper::Method::POST);
+        assert_eq!(checker.headers["x-api-key"], "secret");
+        assert_eq!(checker.body, Some(r#"{"ping": true}"#.to_owned()));
+    }
+
+    #[test]
+    fn test_checker_latency_and_notify_on() {
+        let yaml = r#"
+            checkers:
+              homepage:
+                url: https://example.com
+                warn_latency: 2s
+                max_latency: 5s
+                notify_on: [down, "degraded -> up"]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        let checker = &config.checkers[0];
+        assert_eq!(checker.warn_latency, Some(Duration::from_secs(2)));
+        assert_eq!(checker.max_latency, Some(Duration::from_secs(5)));
+        assert!(checker.notifies(&State::Up, &State::Down));
+        assert!(checker.notifies(&State::Degraded, &State::Up));
+        assert!(!checker.notifies(&State::Down, &State::Up));
+        assert!(!checker.notifies(&State::Up, &State::Degraded));
+    }
+
+    #[test]
+    fn test_checker_invalid_transition() {
+        let yaml = r#"
+            checkers:
+              homepage:
+                url: https://example.com
+                notify_on: up->up
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerTransition {
+                checker_id: "homepage".to_owned(),
+                value: "up->up".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_warn_latency_not_below_max_latency() {
+        let yaml = r#"
+            checkers:
+              homepage:
+                url: https://example.com
+                warn_latency: 5s
+                max_latency: 5s
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerLatency {
+                checker_id: "homepage".to_owned()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "`checkers.homepage.warn_latency` must be less than `max_latency`"
+        );
+    }
+
+    #[test]
+    fn test_invalid_checkers() {
+        let err = parse_config("checkers: [homepage]").unwrap_err();
+        match err {
+            ConfigError::InvalidCheckers { .. } => {}
+            _ => panic!("Expected InvalidCheckers, got {:?}", err),
+        }
+
+        let err = parse_config("checkers:\n  homepage: https://example.com").unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidChecker {
+                checker_id: "homepage".to_owned(),
+                value: r#"String("https://example.com")"#.to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_notifier_attribute() {
+        let yaml = r#"
+            checkers:
+              homepage:
+                url: https://example.com
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["/bin/notify"]
+                channel: ops
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown command notifier attribute `channel` in notifiers.ops"
+        );
+    }
+
+    #[test]
+    fn test_remind_every() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/health
+                remind_every: 1h
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["/bin/notify"]
+                remind_every: 30m
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(
+            config.checkers[0].remind_every,
+            Some(Duration::from_secs(60 * 60))
+        );
+        assert_eq!(
+            config.notifiers[0].remind_every,
+            Some(Duration::from_secs(30 * 60))
+        );
+
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/health
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["/bin/notify"]
+                remind_every: often
+        "#;
+        assert_eq!(
+            parse_config(yaml).unwrap_err(),
+            ConfigError::InvalidNotifierDuration {
+                notifier_id: "ops".to_owned(),
+                attr_name: "remind_every".to_owned(),
+                value: "often".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_escalation() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/health
+                notifiers: [team]
+                escalation:
+                  - after: 10m
+                    notifiers: [oncall]
+                  - after: 30m
+                    notifiers: [pager]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(
+            config.checkers[0].escalation,
+            vec![
+                EscalationStep {
+                    after: Duration::from_secs(10 * 60),
+                    notifiers: vec!["oncall".to_owned()],
+                },
+                EscalationStep {
+                    after: Duration::from_secs(30 * 60),
+                    notifiers: vec!["pager".to_owned()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escalation_without_after() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/health
+                escalation:
+                  - notifiers: [oncall]
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::FieldMissing {
+                path: "checkers.api.escalation.after".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_escalation_not_array() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/health
+                escalation:
+                  after: 10m
+                  notifiers: [oncall]
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        match err {
+            ConfigError::InvalidCheckerEscalation { ref checker_id, .. } => {
+                assert_eq!(checker_id, "api")
+            }
+            _ => panic!("Expected InvalidCheckerEscalation, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_checker_redirects() {
+        let yaml = r#"
+            checkers:
+              website:
+                url: http://example.com
+                follow_redirects: true
+                max_redirects: 3
+                final_url: https://www.example.com/
+        "#;
+        let config = parse_config(yaml).unwrap();
+        let checker = http_config(&config.checkers[0]);
+        assert!(checker.follow_redirects);
+        assert_eq!(checker.max_redirects, 3);
+        assert_eq!(
+            checker.final_url,
+            Some("https://www.example.com/".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_checker_invalid_header() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/ping
+                headers:
+                  "X Api Key": secret
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerHeader {
+                checker_id: "api".to_owned(),
+                header: "X Api Key".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_oauth2_auth_without_client_secret() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/ping
+                auth:
+                  type: oauth2
+                  token_url: https://auth.example.com/token
+                  client_id: hellcheck
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::FieldMissing {
+                path: "checkers.api.auth.client_secret".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_invalid_auth_type() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/ping
+                auth:
+                  type: kerberos
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerAuthType {
+                checker_id: "api".to_owned(),
+                type_value: "kerberos".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_checker_invalid_bearer_token() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/ping
+                auth:
+                  type: bearer
+                  token: "secret\r\n"
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::GeneralError {
+                message: "`checkers.api.auth.token` is not a valid header value".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_env_var_and_file_interpolation() {
+        std::env::set_var("HELLCHECK_TEST_CHAT_ID", "8677112");
+        let token_path = std::env::temp_dir().join("hellcheck_test_telegram_token");
+        std::fs::write(&token_path, "SECRET_TOKEN\n").unwrap();
+
+        let yaml = format!(
+            r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/
+                notifiers: [telebot]
+
+            notifiers:
+              telebot:
+                type: telegram
+                token_file: {}
+                chat_id: ${{HELLCHECK_TEST_CHAT_ID}}
+        "#,
+            token_path.display()
+        );
+        let config = parse_config(&yaml).unwrap();
+        std::fs::remove_file(&token_path).unwrap();
+        match config.notifiers[0].config {
+            NotifierConfig::Telegram(ref telegram) => {
+                assert_eq!(telegram.token, "SECRET_TOKEN");
+                assert_eq!(telegram.chat_id, "8677112");
+            }
+            _ => panic!("Expected telegram notifier"),
+        }
+    }
+
+    #[test]
+    fn test_command_is_not_interpolated() {
+        let yaml = r#"
+            checkers:
+              backups:
+                type: command
+                command: ["sh", "-c", "test -n \"${BACKUP_DIR}\""]
+                notifiers: [script]
+            notifiers:
+              script:
+                type: command
+                command: ["sh", "-c", "echo ${HELLCHECK_ID}"]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        match config.notifiers[0].config {
+            NotifierConfig::Command(ref command) => {
+                assert_eq!(command.arguments[1], "echo ${HELLCHECK_ID}")
+            }
+            _ => panic!("Expected command notifier"),
+        }
+    }
+
+    #[test]
+    fn test_missing_env_var() {
+        let yaml = r#"
+            checkers:
+              greyblake:
+                url: https://www.greyblake.com/
+                basic_auth:
+                  username: greyblake
+                  password: ${HELLCHECK_TEST_MISSING_PASSWORD}
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::EnvVarMissing {
+                name: "HELLCHECK_TEST_MISSING_PASSWORD".to_owned(),
+                path: "checkers.greyblake.basic_auth.password".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_metrics() {
+        let yaml = r#"
+            metrics:
+              listen: 127.0.0.1:9090
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(
+            config.metrics,
+            Some(MetricsConfig {
+                listen: "127.0.0.1:9090".parse().unwrap(),
+                path: "/metrics".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn test_tcp_checker() {
+        let yaml = r#"
+            checkers:
+              postgres:
+                type: tcp
+                address: db.example.com:5432
+        "#;
+        let config = parse_config(yaml).unwrap();
+        match config.checkers[0].kind {
+            CheckerKind::Tcp(ref tcp) => assert_eq!(
+                tcp,
+                &TcpCheckerConfig {
+                    host: "db.example.com".to_owned(),
+                    port: 5432
+                }
+            ),
+            _ => panic!("Expected TCP checker"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_checker_invalid_address() {
+        let yaml = r#"
+            checkers:
+              postgres:
+                type: tcp
+                address: db.example.com
+        "#;
+        let err = parse_config(yaml).unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::InvalidCheckerAddress {
+                checker_id: "postgres".to_owned(),
+                address: "db.example.com".to_owned()
+            }
+        )
+    }
+
+    #[test]
+    fn test_tls_checker() {
+        let yaml = r#"
+            checkers:
+              website_cert:
+                type: tls
+                address: example.com
+                expiry_window: 30days
+        "#;
+        let config = parse_config(yaml).unwrap();
+        match config.checkers[0].kind {
+            CheckerKind::Tls(ref tls) => assert_eq!(
+                tls,
+                &TlsCheckerConfig {
+                    host: "example.com".to_owned(),
+                    port: 443,
+                    server_name: None,
+                    expiry_window: Duration::from_secs(30 * 24 * 60 * 60),
+                }
+            ),
+            _ => panic!("Expected TLS checker"),
+        }
+    }
+
+    #[test]
+    fn test_command_checker() {
+        let yaml = r#"
+            checkers:
+              backups:
+                type: command
+                command: ["/bin/check-backups", "--max-age", "24h"]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        match config.checkers[0].kind {
+            CheckerKind::Command(ref command) => assert_eq!(
+                command,
+                &CommandCheckerConfig {
+                    command: "/bin/check-backups".to_owned(),
+                    arguments: vec!["--max-age".to_owned(), "24h".to_owned()],
+                }
+            ),
+            _ => panic!("Expected command checker"),
+        }
+    }
+
+    #[test]
+    fn test_heartbeat_checker() {
+        let yaml = r#"
+            checkers:
+              backup:
+                type: heartbeat
+                period: 1day
+        "#;
+        let config = parse_config(yaml).unwrap();
+        match config.checkers[0].kind {
+            CheckerKind::Heartbeat(ref heartbeat) => assert_eq!(
+                heartbeat,
+                &HeartbeatCheckerConfig {
+                    period: Duration::from_secs(24 * 60 * 60),
+                    grace: Duration::from_secs(60),
+                }
+            ),
+            _ => panic!("Expected heartbeat checker"),
+        }
+        assert!(!config.checkers[0].is_polled());
+    }
+
+    #[test]
+    fn test_maintenance() {
+        let yaml = r#"
+            checkers:
+              db:
+                url: https://db.example.com/
+                tags: [db]
+            maintenance:
+              nightly_backup:
+                schedule: "0 3 * * *"
+                duration: 30m
+                tags: db
+              migration:
+                start: 2019-03-01T22:00:00Z
+                end: 2019-03-02T02:00:00Z
+                summary: false
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(config.checkers[0].tags, vec!["db".to_owned()]);
+        assert_eq!(
+            config.maintenance[0],
+            MaintenanceWindow {
+                id: "nightly_backup".to_owned(),
+                schedule: MaintenanceSchedule::Cron {
+                    cron: Cron::parse("0 3 * * *").unwrap(),
+                    duration: Duration::from_secs(30 * 60),
+                },
+                scope: MaintenanceScope {
+                    checkers: vec![],
+                    tags: vec!["db".to_owned()],
+                },
+                summary: true,
+            }
+        );
+        match config.maintenance[1].schedule {
+            MaintenanceSchedule::Range { start, end } => {
+                assert_eq!(
+                    end.duration_since(start).unwrap(),
+                    Duration::from_secs(4 * 60 * 60)
+                )
+            }
+            _ => panic!("Expected time range"),
+        }
+        assert!(!config.maintenance[1].summary);
+
+        let yaml = r#"
+            checkers:
+              db:
+                url: https://db.example.com/
+            maintenance:
+              nightly_backup:
+                schedule: "0 3 * * *"
+        "#;
+        assert!(parse_config(yaml).is_err());
+    }
+
+    #[test]
+    fn test_depends_on() {
+        let yaml = r#"
+            checkers:
+              lb:
+                url: https://lb.example.com/
+              api:
+                url: https://api.example.com/
+                depends_on: [lb]
+              web:
+                url: https://www.example.com/
+                depends_on: api
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(config.checkers[1].depends_on, vec!["lb".to_owned()]);
+        assert_eq!(config.checkers[2].depends_on, vec!["api".to_owned()]);
+        assert!(validate_config(&config).is_ok());
+
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                depends_on: [lb]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(
+            validate_config(&config),
+            Err(ConfigValidationError::UnknownDependency {
+                checker_id: "api".to_owned(),
+                dependency_id: "lb".to_owned(),
+            })
+        );
+
+        let yaml = r#"
+            checkers:
+              lb:
+                url: https://lb.example.com/
+                depends_on: [web]
+              api:
+                url: https://api.example.com/
+                depends_on: [lb]
+              web:
+                url: https://www.example.com/
+                depends_on: [api]
+        "#;
+        let config = parse_config(yaml).unwrap();
+        assert_eq!(
+            validate_config(&config),
+            Err(ConfigValidationError::DependencyCycle {
+                cycle: "lb -> web -> api -> lb".to_owned(),
+            })
+        );
+    }
 }
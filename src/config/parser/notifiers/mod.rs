@@ -1,4 +1,6 @@
-use yaml_rust::yaml::Yaml;
+use yaml_rust::yaml::{Hash, Yaml};
+
+use std::time::Duration;
 
 use crate::config::{Notifier, NotifierConfig};
 use crate::error::ConfigError;
@@ -30,14 +32,51 @@ pub fn parse_notifiers(notifier_configs: &Yaml) -> Result<Vec<Notifier>> {
     Ok(notifiers)
 }
 
+// Parses attributes, that are common for all types of notifiers. The rest of the attributes
+// is parsed according to `type`.
 fn parse_notifier(key: &Yaml, body: &Yaml) -> Result<Notifier> {
     let id = parse_key(key)?;
-    let config = parse_notifier_config(&id, body)?;
+    let mut remind_every: Option<Duration> = None;
+
+    // Attributes specific for the type of the notifier
+    let mut type_attrs = Hash::new();
+
+    for (attr_yaml_key, attr_yaml_val) in parse_yaml_to_hash(body)? {
+        match parse_key(attr_yaml_key)?.as_ref() {
+            "remind_every" => {
+                remind_every = Some(parse_duration(&id, "remind_every", attr_yaml_val)?);
+            }
+            _ => {
+                type_attrs.insert(attr_yaml_key.clone(), attr_yaml_val.clone());
+            }
+        }
+    }
+
+    let config = parse_notifier_config(&id, &Yaml::Hash(type_attrs))?;
 
-    let notifier = Notifier { id, config };
+    let notifier = Notifier {
+        id,
+        config,
+        remind_every,
+    };
     Ok(notifier)
 }
 
+fn parse_duration(id: &str, attr_name: &str, val: &Yaml) -> Result<Duration> {
+    let attr_val = parse_yaml_to_string(val)?;
+    match attr_val.parse::<humantime::Duration>() {
+        Ok(val) => Ok(val.into()),
+        Err(_) => {
+            let e = ConfigError::InvalidNotifierDuration {
+                notifier_id: id.to_owned(),
+                attr_name: attr_name.to_owned(),
+                value: attr_val,
+            };
+            Err(e)
+        }
+    }
+}
+
 fn parse_notifier_config(id: &str, body: &Yaml) -> Result<NotifierConfig> {
     let hash = parse_yaml_to_hash(body)?;
     let type_val_yaml =
@@ -0,0 +1,46 @@
+use yaml_rust::yaml::Yaml;
+
+use super::common::{parse_key, parse_yaml_to_socket_addr, parse_yaml_to_string, Result};
+use crate::config::ServerConfig;
+use crate::error::ConfigError;
+
+pub fn parse_server(body: &Yaml) -> Result<ServerConfig> {
+    let mut listen = None;
+    let mut admin_token = None;
+
+    match body {
+        Yaml::Hash(hash) => {
+            for (attr_yaml_key, attr_yaml_val) in hash {
+                let attr_key = parse_key(attr_yaml_key)?;
+
+                match attr_key.as_ref() {
+                    "listen" => {
+                        listen = Some(parse_yaml_to_socket_addr("server.listen", attr_yaml_val)?);
+                    }
+                    "admin_token" => {
+                        admin_token = Some(parse_yaml_to_string(attr_yaml_val)?);
+                    }
+                    _ => {
+                        let message = format!("Unknown attribute server.{}", attr_key);
+                        return Err(ConfigError::GeneralError { message });
+                    }
+                }
+            }
+        }
+        _ => {
+            let message = format!("`server` must be a hash. Got {:?}", body);
+            return Err(ConfigError::GeneralError { message });
+        }
+    }
+
+    match listen {
+        Some(listen) => Ok(ServerConfig {
+            listen,
+            admin_token,
+        }),
+        None => {
+            let message = "`server.listen` is missing".to_owned();
+            Err(ConfigError::GeneralError { message })
+        }
+    }
+}
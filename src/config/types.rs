@@ -1,19 +1,316 @@
-use hyper::Uri;
-use std::time::Duration;
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::{HeaderMap, Method, Uri};
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+
+use crate::maintenance::Cron;
+use crate::reactor::State;
 
 #[derive(Debug, Clone)]
 pub struct FileConfig {
     pub checkers: Vec<CheckerConfig>,
     pub notifiers: Vec<Notifier>,
+    pub metrics: Option<MetricsConfig>,
+    pub server: Option<ServerConfig>,
+    pub maintenance: Vec<MaintenanceWindow>,
+}
+
+// Time, when notifications of the checkers within the scope are suppressed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceWindow {
+    pub id: String,
+    pub schedule: MaintenanceSchedule,
+    pub scope: MaintenanceScope,
+    // Notify about the checkers, whose state has changed during the window, when it ends
+    pub summary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaintenanceSchedule {
+    // Starts whenever the cron expression matches and lasts for `duration`
+    Cron { cron: Cron, duration: Duration },
+    Range { start: SystemTime, end: SystemTime },
+}
+
+// Checkers, that are affected by a maintenance window or a silence.
+// If neither ids nor tags are given, all the checkers are affected.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MaintenanceScope {
+    pub checkers: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl MaintenanceScope {
+    pub fn covers(&self, checker: &CheckerConfig) -> bool {
+        (self.checkers.is_empty() && self.tags.is_empty())
+            || self.checkers.contains(&checker.id)
+            || checker.tags.iter().any(|tag| self.tags.contains(tag))
+    }
+}
+
+// Built-in HTTP server, that serves the status page and the status API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub listen: SocketAddr,
+    // Token to manage silences with. Without it the silences API is disabled
+    pub admin_token: Option<String>,
+}
+
+// Built-in HTTP server, that exposes metrics in Prometheus text format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsConfig {
+    pub listen: SocketAddr,
+    pub path: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct CheckerConfig {
     pub id: String,
-    pub url: Uri,
-    pub basic_auth: Option<BasicAuth>,
+    pub kind: CheckerKind,
     pub interval: Duration,
+    pub timeout: Duration,
     pub notifiers: Vec<String>,
+    pub fail_threshold: u32,
+    pub success_threshold: u32,
+    pub tags: Vec<String>,
+    // Healthy, but slower responses degrade the service
+    pub warn_latency: Option<Duration>,
+    // Healthy, but slower responses are considered as failures
+    pub max_latency: Option<Duration>,
+    // Changes of the state, that are sent to the notifiers
+    pub notify_on: Vec<Transition>,
+    // How often to remind that the service is still down. Overrides `remind_every` of the notifiers
+    pub remind_every: Option<Duration>,
+    // Notifiers to involve, if the service stays down for longer
+    pub escalation: Vec<EscalationStep>,
+    // Ids of the checkers, that this one depends on. Their outages explain outages of this one
+    pub depends_on: Vec<String>,
+}
+
+impl CheckerConfig {
+    // Human readable description of what is checked, e.g. URL.
+    pub fn target(&self) -> String {
+        match self.kind {
+            CheckerKind::Http(ref http) => http.url.to_string(),
+            CheckerKind::Tcp(ref tcp) => format!("{}:{}", tcp.host, tcp.port),
+            CheckerKind::Dns(ref dns) => format!("{} {}", dns.record_type, dns.name),
+            CheckerKind::Tls(ref tls) => format!("{}:{}", tls.host, tls.port),
+            CheckerKind::Command(ref command) => {
+                let mut argv = vec![command.command.clone()];
+                argv.extend(command.arguments.iter().cloned());
+                argv.join(" ")
+            }
+            CheckerKind::Heartbeat(_) => format!("/ping/{}", self.id),
+        }
+    }
+
+    pub fn notifies(&self, from: &State, to: &State) -> bool {
+        self.notify_on.iter().any(|t| t.matches(from, to))
+    }
+
+    // Whether the notifier is used directly or by any of the escalation steps.
+    pub fn uses_notifier(&self, notifier_id: &str) -> bool {
+        self.notifiers.iter().any(|id| id == notifier_id)
+            || self
+                .escalation
+                .iter()
+                .any(|step| step.notifiers.iter().any(|id| id == notifier_id))
+    }
+
+    // Heartbeat checkers are not polled, they wait for pings instead.
+    pub fn is_polled(&self) -> bool {
+        !matches!(self.kind, CheckerKind::Heartbeat(_))
+    }
+}
+
+// Notifiers of the step are notified, if the service is still down `after` the outage has started.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscalationStep {
+    pub after: Duration,
+    pub notifiers: Vec<String>,
+}
+
+// Change of the confirmed state of a checker, e.g. `up->down`. If `from` is not set,
+// a change from any other state matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from: Option<State>,
+    pub to: State,
+}
+
+impl Transition {
+    // By default every change of the state is notified.
+    pub fn all() -> Vec<Transition> {
+        vec![State::Up, State::Degraded, State::Down]
+            .into_iter()
+            .map(|to| Transition { from: None, to })
+            .collect()
+    }
+
+    pub fn matches(&self, from: &State, to: &State) -> bool {
+        let from_matches = match self.from {
+            Some(ref f) => f == from,
+            None => true,
+        };
+        self.to == *to && from_matches
+    }
+}
+
+// Kind of a checker, declared with `type` attribute.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum CheckerKind {
+    Http(HttpCheckerConfig),
+    Tcp(TcpCheckerConfig),
+    Dns(DnsCheckerConfig),
+    Tls(TlsCheckerConfig),
+    Command(CommandCheckerConfig),
+    Heartbeat(HeartbeatCheckerConfig),
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpCheckerConfig {
+    pub url: Uri,
+    pub method: Method,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+    pub auth: Option<Auth>,
+    pub expected_status: Vec<StatusRange>,
+    pub body_assertions: Vec<BodyAssertion>,
+    pub json_assertions: Vec<JsonAssertion>,
+    pub follow_redirects: bool,
+    pub max_redirects: u32,
+    // URL, that the redirects must end up at
+    pub final_url: Option<Uri>,
+}
+
+// Verifies that `host:port` accepts TCP connections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TcpCheckerConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+// Runs a command, exit code 0 means the service is up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandCheckerConfig {
+    pub command: String,
+    pub arguments: Vec<String>,
+}
+
+// Waits for pings sent to the built-in server by the watched job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeartbeatCheckerConfig {
+    // How often the job is expected to ping
+    pub period: Duration,
+    // How late a ping may be, before the checker goes down
+    pub grace: Duration,
+}
+
+// Verifies the certificate chain presented by `host:port`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsCheckerConfig {
+    pub host: String,
+    pub port: u16,
+    // Name to send in SNI and to verify the certificate against (default: host)
+    pub server_name: Option<String>,
+    // The check fails if a certificate expires sooner than that
+    pub expiry_window: Duration,
+}
+
+// Resolves `name` and verifies the answers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsCheckerConfig {
+    pub name: String,
+    pub record_type: DnsRecordType,
+    // System resolver from /etc/resolv.conf is used if not set
+    pub nameserver: Option<SocketAddr>,
+    // Values, that must be present among the answers
+    pub expected: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DnsRecordType {
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Txt,
+}
+
+impl DnsRecordType {
+    // Numeric code of the type, used in DNS messages.
+    pub fn code(self) -> u16 {
+        match self {
+            DnsRecordType::A => 1,
+            DnsRecordType::Cname => 5,
+            DnsRecordType::Mx => 15,
+            DnsRecordType::Txt => 16,
+            DnsRecordType::Aaaa => 28,
+        }
+    }
+}
+
+impl fmt::Display for DnsRecordType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DnsRecordType::A => "A",
+            DnsRecordType::Aaaa => "AAAA",
+            DnsRecordType::Cname => "CNAME",
+            DnsRecordType::Mx => "MX",
+            DnsRecordType::Txt => "TXT",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Inclusive range of HTTP status codes, e.g. `200-399` or `2xx`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusRange {
+    pub from: u16,
+    pub to: u16,
+}
+
+impl StatusRange {
+    pub fn contains(&self, status: u16) -> bool {
+        self.from <= status && status <= self.to
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BodyAssertion {
+    Contains(String),
+    NotContains(String),
+    Regex(Regex),
+}
+
+// Assertion on a value of JSON response body, located by JSON pointer (e.g. `/db/status`).
+#[derive(Debug, Clone)]
+pub struct JsonAssertion {
+    pub pointer: String,
+    pub operator: JsonOperator,
+}
+
+#[derive(Debug, Clone)]
+pub enum JsonOperator {
+    Equals(JsonValue),
+    NotEquals(JsonValue),
+    GreaterThan(f64),
+    GreaterOrEqual(f64),
+    LessThan(f64),
+    LessOrEqual(f64),
+}
+
+#[derive(Debug, Clone)]
+pub enum Auth {
+    Basic(BasicAuth),
+    // Complete value of `Authorization` header, e.g. `Bearer <token>`
+    Bearer(HeaderValue),
+    Header(HeaderName, HeaderValue),
+    OAuth2(OAuth2Config),
 }
 
 #[derive(Debug, Clone)]
@@ -22,10 +319,21 @@ pub struct BasicAuth {
     pub password: String,
 }
 
+// OAuth2 client credentials flow: access token is obtained from `token_url`.
+#[derive(Debug, Clone)]
+pub struct OAuth2Config {
+    pub token_url: Uri,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scope: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Notifier {
     pub id: String,
     pub config: NotifierConfig,
+    // How often to remind that a service is still down
+    pub remind_every: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
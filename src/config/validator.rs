@@ -1,6 +1,6 @@
 // Validates FileConfig for inconsistencies.
 
-use crate::config::{FileConfig, NotifierConfig};
+use crate::config::{CheckerKind, FileConfig, NotifierConfig};
 use crate::error::ConfigValidationError;
 
 type Result<T> = ::std::result::Result<T, ConfigValidationError>;
@@ -8,11 +8,18 @@ type Result<T> = ::std::result::Result<T, ConfigValidationError>;
 pub fn validate_config(config: &FileConfig) -> Result<Vec<String>> {
     verify_checker_presence(config)?;
     verify_checker_notifiers(config)?;
+    verify_escalation_notifiers(config)?;
+    verify_dependencies(config)?;
+    verify_dependency_cycles(config)?;
     verify_command_notifiers(config)?;
+    verify_command_checkers(config)?;
+    verify_heartbeat_checkers(config)?;
+    verify_maintenance_checkers(config)?;
 
     let mut warnings: Vec<String> = vec![];
     verify_empty_notifiers(config, &mut warnings);
     verify_unused_notifiers(config, &mut warnings);
+    verify_maintenance_tags(config, &mut warnings);
 
     Ok(warnings)
 }
@@ -44,6 +51,89 @@ fn verify_checker_notifiers(config: &FileConfig) -> Result<()> {
     Ok(())
 }
 
+// Ensure, that escalation steps refer to declared notifiers.
+fn verify_escalation_notifiers(config: &FileConfig) -> Result<()> {
+    for checker in config.checkers.iter() {
+        for step in checker.escalation.iter() {
+            for notifier_id in step.notifiers.iter() {
+                if config.get_notifier_by_id(notifier_id).is_none() {
+                    let err = ConfigValidationError::UnknownEscalationNotifier {
+                        checker_id: checker.id.to_owned(),
+                        notifier_id: notifier_id.to_owned(),
+                    };
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Ensure, that checkers depend on declared checkers.
+fn verify_dependencies(config: &FileConfig) -> Result<()> {
+    for checker in config.checkers.iter() {
+        for dependency_id in checker.depends_on.iter() {
+            if config.get_checker_by_id(dependency_id).is_none() {
+                let err = ConfigValidationError::UnknownDependency {
+                    checker_id: checker.id.to_owned(),
+                    dependency_id: dependency_id.to_owned(),
+                };
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Ensure, that checkers do not depend on themselves, directly or through other checkers.
+fn verify_dependency_cycles(config: &FileConfig) -> Result<()> {
+    // Checkers, that are known to have no cycles among their dependencies
+    let mut verified: Vec<&str> = vec![];
+
+    for checker in config.checkers.iter() {
+        let mut path: Vec<&str> = vec![];
+        if let Some(cycle) = find_cycle(config, &checker.id, &mut path, &mut verified) {
+            let err = ConfigValidationError::DependencyCycle {
+                cycle: cycle.join(" -> "),
+            };
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+// Depth-first search, that returns the cycle if the checker is reachable from itself.
+fn find_cycle<'a>(
+    config: &'a FileConfig,
+    checker_id: &'a str,
+    path: &mut Vec<&'a str>,
+    verified: &mut Vec<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if let Some(pos) = path.iter().position(|id| *id == checker_id) {
+        let mut cycle = path[pos..].to_vec();
+        cycle.push(checker_id);
+        return Some(cycle);
+    }
+    if verified.contains(&checker_id) {
+        return None;
+    }
+
+    path.push(checker_id);
+    if let Some(checker) = config.checkers.iter().find(|c| c.id == checker_id) {
+        for dependency_id in checker.depends_on.iter() {
+            if let Some(cycle) = find_cycle(config, dependency_id, path, verified) {
+                return Some(cycle);
+            }
+        }
+    }
+    path.pop();
+    verified.push(checker_id);
+    None
+}
+
 // Ensure all CommandNotifier refers to an existing command
 fn verify_command_notifiers(config: &FileConfig) -> Result<()> {
     for notifier in config.notifiers.iter() {
@@ -60,6 +150,54 @@ fn verify_command_notifiers(config: &FileConfig) -> Result<()> {
     Ok(())
 }
 
+// Ensure all command checkers refer to an existing command
+fn verify_command_checkers(config: &FileConfig) -> Result<()> {
+    for checker in config.checkers.iter() {
+        if let CheckerKind::Command(c) = &checker.kind {
+            if !command_exists(&c.command) {
+                let err = ConfigValidationError::CheckerCommandNotFound {
+                    checker_id: checker.id.clone(),
+                    command: c.command.clone(),
+                };
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Ensure pings of heartbeat checkers can be received
+fn verify_heartbeat_checkers(config: &FileConfig) -> Result<()> {
+    if config.server.is_some() {
+        return Ok(());
+    }
+    match config.checkers.iter().find(|c| !c.is_polled()) {
+        Some(checker) => {
+            let err = ConfigValidationError::HeartbeatWithoutServer {
+                checker_id: checker.id.clone(),
+            };
+            Err(err)
+        }
+        None => Ok(()),
+    }
+}
+
+// Ensure, that maintenance windows refer to declared checkers.
+fn verify_maintenance_checkers(config: &FileConfig) -> Result<()> {
+    for window in config.maintenance.iter() {
+        for checker_id in window.scope.checkers.iter() {
+            if !config.checkers.iter().any(|c| c.id == *checker_id) {
+                let err = ConfigValidationError::UnknownMaintenanceChecker {
+                    window_id: window.id.clone(),
+                    checker_id: checker_id.clone(),
+                };
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
+
 fn command_exists(command: &str) -> bool {
     let res = std::process::Command::new("which").arg(command).output();
 
@@ -77,7 +215,7 @@ fn verify_empty_notifiers(config: &FileConfig, warnings: &mut Vec<String>) {
     }
 
     for checker in config.checkers.iter() {
-        if checker.notifiers.is_empty() {
+        if checker.notifiers.is_empty() && checker.escalation.is_empty() {
             let msg = format!(
                 "`checkers.{}.notifiers` is empty. You will not get notifications",
                 checker.id
@@ -91,8 +229,10 @@ fn verify_unused_notifiers(config: &FileConfig, warnings: &mut Vec<String>) {
     let used_notifier_ids: Vec<&String> = config
         .checkers
         .iter()
-        .map(|c| c.notifiers.iter())
-        .flatten()
+        .flat_map(|c| {
+            let escalation_notifiers = c.escalation.iter().flat_map(|s| s.notifiers.iter());
+            c.notifiers.iter().chain(escalation_notifiers)
+        })
         .collect();
 
     for notifier in config.notifiers.iter() {
@@ -105,3 +245,17 @@ fn verify_unused_notifiers(config: &FileConfig, warnings: &mut Vec<String>) {
         }
     }
 }
+
+fn verify_maintenance_tags(config: &FileConfig, warnings: &mut Vec<String>) {
+    for window in config.maintenance.iter() {
+        for tag in window.scope.tags.iter() {
+            if !config.checkers.iter().any(|c| c.tags.contains(tag)) {
+                let msg = format!(
+                    "`maintenance.{}.tags` refers to tag `{}`, that none of the checkers has.",
+                    window.id, tag
+                );
+                warnings.push(msg);
+            }
+        }
+    }
+}
@@ -11,6 +11,12 @@ pub enum ConfigError {
     #[fail(display = "{}", message)]
     GeneralError { message: String },
 
+    #[fail(display = "`checkers` must be a hash. Got {}", value)]
+    InvalidCheckers { value: String },
+
+    #[fail(display = "`checkers.{}` must be a hash. Got {}", checker_id, value)]
+    InvalidChecker { checker_id: String, value: String },
+
     #[fail(
         display = "Unknown checker attribute `{}` in checkers.{}",
         attr_name, checker_id
@@ -29,12 +35,112 @@ pub enum ConfigError {
         interval: String,
     },
 
+    #[fail(
+        display = "Failed to parse timeout `{}` in checkers.{}.timeout",
+        timeout, checker_id
+    )]
+    InvalidCheckerTimeout { checker_id: String, timeout: String },
+
     #[fail(
         display = "Failed to parse URL `{}` in checkers.{}.url",
         url, checker_id
     )]
     InvalidCheckerUrl { checker_id: String, url: String },
 
+    #[fail(
+        display = "Invalid checker type `{}` in checkers.{}.type. Expected one of: http, tcp, dns, tls, command, heartbeat",
+        type_value, checker_id
+    )]
+    InvalidCheckerType {
+        checker_id: String,
+        type_value: String,
+    },
+
+    #[fail(
+        display = "Invalid address `{}` in checkers.{}.address. Expected `host:port`",
+        address, checker_id
+    )]
+    InvalidCheckerAddress { checker_id: String, address: String },
+
+    #[fail(
+        display = "Failed to parse duration `{}` in checkers.{}.{}",
+        value, checker_id, attr_name
+    )]
+    InvalidCheckerDuration {
+        checker_id: String,
+        attr_name: String,
+        value: String,
+    },
+
+    #[fail(
+        display = "Invalid transition `{}` in checkers.{}.notify_on. Expected a state (up, degraded, down) or a transition like `up->down`",
+        value, checker_id
+    )]
+    InvalidCheckerTransition { checker_id: String, value: String },
+
+    #[fail(
+        display = "Invalid DNS record type `{}` in checkers.{}.record_type. Expected one of: A, AAAA, CNAME, MX, TXT",
+        record_type, checker_id
+    )]
+    InvalidCheckerRecordType {
+        checker_id: String,
+        record_type: String,
+    },
+
+    #[fail(
+        display = "Invalid nameserver `{}` in checkers.{}.nameserver. Expected IP address with optional port",
+        nameserver, checker_id
+    )]
+    InvalidCheckerNameserver {
+        checker_id: String,
+        nameserver: String,
+    },
+
+    #[fail(
+        display = "Invalid HTTP method `{}` in checkers.{}.method",
+        method, checker_id
+    )]
+    InvalidCheckerMethod { checker_id: String, method: String },
+
+    #[fail(
+        display = "Invalid HTTP header `{}` in checkers.{}.headers",
+        header, checker_id
+    )]
+    InvalidCheckerHeader { checker_id: String, header: String },
+
+    #[fail(
+        display = "Invalid auth type `{}` in `checkers.{}.auth.type`",
+        type_value, checker_id
+    )]
+    InvalidCheckerAuthType {
+        checker_id: String,
+        type_value: String,
+    },
+
+    #[fail(
+        display = "Failed to parse expected status `{}` in checkers.{}.expected_status",
+        value, checker_id
+    )]
+    InvalidCheckerExpectedStatus { checker_id: String, value: String },
+
+    #[fail(
+        display = "Failed to parse regex `{}` in checkers.{}.body_regex",
+        regex, checker_id
+    )]
+    InvalidCheckerBodyRegex { checker_id: String, regex: String },
+
+    #[fail(
+        display = "`checkers.{}.warn_latency` must be less than `max_latency`",
+        checker_id
+    )]
+    InvalidCheckerLatency { checker_id: String },
+
+    #[fail(
+        display = "`checkers.{}.escalation` must be an array. Got {}",
+        checker_id, value
+    )]
+    InvalidCheckerEscalation { checker_id: String, value: String },
+
     #[fail(
         display = "Invalid notifier type `{}` in `notifiers.{}.type`",
         type_value, notifier_id
@@ -45,7 +151,17 @@ pub enum ConfigError {
     },
 
     #[fail(
-        display = "Unknown {} notifier attribute `{}` in checkers.{}",
+        display = "Failed to parse duration `{}` in notifiers.{}.{}",
+        value, notifier_id, attr_name
+    )]
+    InvalidNotifierDuration {
+        notifier_id: String,
+        attr_name: String,
+        value: String,
+    },
+
+    #[fail(
+        display = "Unknown {} notifier attribute `{}` in notifiers.{}",
         notifier_type, attr_name, notifier_id
     )]
     UnknownNotifierAttribute {
@@ -56,6 +172,22 @@ pub enum ConfigError {
 
     #[fail(display = "Field `{}` is missing", path)]
     FieldMissing { path: String },
+
+    #[fail(
+        display = "Environment variable `{}` referenced in `{}` is not set",
+        name, path
+    )]
+    EnvVarMissing { name: String, path: String },
+
+    #[fail(
+        display = "Failed to read file `{}` referenced in `{}`: {}",
+        file, path, message
+    )]
+    SecretFileError {
+        file: String,
+        path: String,
+        message: String,
+    },
 }
 
 #[derive(Debug, Fail, PartialEq)]
@@ -69,6 +201,36 @@ pub enum ConfigValidationError {
         notifier_id: String,
     },
 
+    #[fail(
+        display = "`checkers.{}.escalation` refers to an undeclared notifier `{}`",
+        checker_id, notifier_id
+    )]
+    UnknownEscalationNotifier {
+        checker_id: String,
+        notifier_id: String,
+    },
+
+    #[fail(
+        display = "`checkers.{}.depends_on` refers to an undeclared checker `{}`",
+        checker_id, dependency_id
+    )]
+    UnknownDependency {
+        checker_id: String,
+        dependency_id: String,
+    },
+
+    #[fail(display = "Checkers depend on each other: {}", cycle)]
+    DependencyCycle { cycle: String },
+
+    #[fail(
+        display = "`maintenance.{}.checkers` refers to an undeclared checker `{}`",
+        window_id, checker_id
+    )]
+    UnknownMaintenanceChecker {
+        window_id: String,
+        checker_id: String,
+    },
+
     #[fail(
         display = "`notifiers.{}.command` refers to `{}`, but it is not executable",
         notifier_id, command
@@ -78,6 +240,18 @@ pub enum ConfigValidationError {
         command: String,
     },
 
+    #[fail(
+        display = "`checkers.{}.command` refers to `{}`, but it is not executable",
+        checker_id, command
+    )]
+    CheckerCommandNotFound { checker_id: String, command: String },
+
+    #[fail(
+        display = "`checkers.{}` is a heartbeat checker, but `server` is not configured to receive pings",
+        checker_id
+    )]
+    HeartbeatWithoutServer { checker_id: String },
+
     #[fail(display = "There are no checkers defined in the config file")]
     NoCheckers,
 }
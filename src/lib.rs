@@ -5,6 +5,9 @@ pub mod cli;
 
 mod config;
 mod error;
+mod maintenance;
+mod metrics;
 mod notifiers;
 mod reactor;
+mod server;
 mod watcher;
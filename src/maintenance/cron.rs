@@ -0,0 +1,228 @@
+use std::fmt;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Cron expression with 5 fields: minute, hour, day of month, month and day of week
+// (0-7, both 0 and 7 are Sunday). Every field is `*`, a number, a range (`1-5`),
+// a step (`*/15`, `0-30/10`) or a comma separated list of them. Times are in UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cron {
+    expression: String,
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    // Like in cron, if both days of month and days of week are restricted,
+    // a time matches if either of them matches.
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Cron, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        }
+
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+
+        Ok(Cron {
+            expression: expression.to_owned(),
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            days_restricted: fields[2] != "*",
+            weekdays_restricted: fields[4] != "*",
+        })
+    }
+
+    // Beginning of the latest matching minute within `since..=until` (seconds since UNIX epoch).
+    // Days, that do not match, are skipped as a whole, so long ranges are cheap to search.
+    pub fn last_match(&self, until: u64, since: u64) -> Option<u64> {
+        let mut days_since_epoch = until / SECS_PER_DAY;
+        loop {
+            let day_start = days_since_epoch * SECS_PER_DAY;
+            if day_start + SECS_PER_DAY <= since {
+                return None;
+            }
+            if self.day_matches(days_since_epoch) {
+                for hour in (0..24).rev().filter(|h| self.hours[*h]) {
+                    for minute in (0..60).rev().filter(|m| self.minutes[*m]) {
+                        let start = day_start + hour as u64 * 3600 + minute as u64 * 60;
+                        if start > until {
+                            continue;
+                        }
+                        return if start >= since { Some(start) } else { None };
+                    }
+                }
+            }
+            if days_since_epoch == 0 {
+                return None;
+            }
+            days_since_epoch -= 1;
+        }
+    }
+
+    fn day_matches(&self, days_since_epoch: u64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        // 1970-01-01 was Thursday
+        let weekday = ((days_since_epoch + 4) % 7) as usize;
+
+        let day_matches = match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => self.days[day] || self.weekdays[weekday],
+            _ => self.days[day] && self.weekdays[weekday],
+        };
+        self.months[month] && day_matches
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+// Returns a vector indexed by values of the field, true for the allowed ones.
+fn parse_field(field: &str, min: usize, max: usize) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(pos) => {
+                let step = part[pos + 1..]
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid step in `{}`", part))?;
+                if step == 0 {
+                    return Err(format!("invalid step in `{}`", part));
+                }
+                (&part[..pos], step)
+            }
+            None => (part, 1),
+        };
+
+        let (from, to) = if range == "*" {
+            (min, max)
+        } else if let Some(pos) = range.find('-') {
+            (
+                parse_value(&range[..pos], min, max)?,
+                parse_value(&range[pos + 1..], min, max)?,
+            )
+        } else {
+            let value = parse_value(range, min, max)?;
+            // `5/10` means from 5 to the end with step 10
+            if step > 1 {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+        if from > to {
+            return Err(format!("invalid range `{}`", range));
+        }
+
+        for value in (from..=to).step_by(step) {
+            allowed[value] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+fn parse_value(text: &str, min: usize, max: usize) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(value) if value >= min && value <= max => Ok(value),
+        _ => Err(format!(
+            "`{}` is not a number between {} and {}",
+            text, min, max
+        )),
+    }
+}
+
+// Converts number of days since UNIX epoch into (year, month, day).
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, usize, usize) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month as usize, day as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2019-02-05T03:00:00Z, Tuesday
+    const TUESDAY_3AM: u64 = 1_549_335_600;
+
+    // Whether the minute, that `secs` belongs to, matches the expression
+    fn matches(cron: &Cron, secs: u64) -> bool {
+        cron.last_match(secs, secs - secs % 60).is_some()
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(TUESDAY_3AM / SECS_PER_DAY), (2019, 2, 5));
+    }
+
+    #[test]
+    fn test_matches() {
+        let cron = Cron::parse("0 3 * * 2").unwrap();
+        assert!(matches(&cron, TUESDAY_3AM));
+        assert!(matches(&cron, TUESDAY_3AM + 59));
+        assert!(!matches(&cron, TUESDAY_3AM + 60));
+        assert!(!matches(&cron, TUESDAY_3AM + SECS_PER_DAY));
+
+        let cron = Cron::parse("*/15 2-4 1,5 2 *").unwrap();
+        assert!(matches(&cron, TUESDAY_3AM + 15 * 60));
+        assert!(!matches(&cron, TUESDAY_3AM + 10 * 60));
+    }
+
+    #[test]
+    fn test_last_match() {
+        let cron = Cron::parse("0 3 * * 2").unwrap();
+        let week = 7 * SECS_PER_DAY;
+        assert_eq!(
+            cron.last_match(TUESDAY_3AM + week - 1, TUESDAY_3AM),
+            Some(TUESDAY_3AM)
+        );
+        assert_eq!(
+            cron.last_match(TUESDAY_3AM + week, TUESDAY_3AM),
+            Some(TUESDAY_3AM + week)
+        );
+        assert_eq!(
+            cron.last_match(TUESDAY_3AM + week - 1, TUESDAY_3AM + 1),
+            None
+        );
+        assert_eq!(
+            cron.last_match(TUESDAY_3AM - 1, 0),
+            Some(TUESDAY_3AM - week)
+        );
+
+        let cron = Cron::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            cron.last_match(TUESDAY_3AM + 20 * 60, TUESDAY_3AM),
+            Some(TUESDAY_3AM + 15 * 60)
+        );
+    }
+
+    #[test]
+    fn test_invalid_expression() {
+        assert!(Cron::parse("0 3 * *").is_err());
+        assert!(Cron::parse("60 3 * * *").is_err());
+        assert!(Cron::parse("*/0 3 * * *").is_err());
+    }
+}
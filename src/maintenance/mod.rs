@@ -0,0 +1,130 @@
+// Maintenance windows from the config and silences created at runtime.
+// Notifications of the checkers within their scope are suppressed.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{CheckerConfig, MaintenanceSchedule, MaintenanceScope, MaintenanceWindow};
+
+mod cron;
+
+pub use self::cron::Cron;
+
+// Silence created with the API of the built-in server.
+#[derive(Debug, Clone)]
+pub struct Silence {
+    pub id: u64,
+    pub scope: MaintenanceScope,
+    pub until: SystemTime,
+    pub comment: Option<String>,
+    pub summary: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Silences {
+    last_id: u64,
+    items: Vec<Silence>,
+}
+
+// Silences are created by the server and read by the reactor.
+pub type SharedSilences = Arc<Mutex<Silences>>;
+
+impl Silences {
+    pub fn add(
+        &mut self,
+        scope: MaintenanceScope,
+        duration: Duration,
+        comment: Option<String>,
+        summary: bool,
+    ) -> Silence {
+        self.last_id += 1;
+        let silence = Silence {
+            id: self.last_id,
+            scope,
+            until: SystemTime::now() + duration,
+            comment,
+            summary,
+        };
+        self.items.push(silence.clone());
+        silence
+    }
+
+    // Returns false if there is no such silence.
+    pub fn remove(&mut self, id: u64) -> bool {
+        let len = self.items.len();
+        self.items.retain(|s| s.id != id);
+        self.items.len() != len
+    }
+
+    // Silences, that have not expired yet. The expired ones are forgotten.
+    pub fn active(&mut self, now: SystemTime) -> Vec<Silence> {
+        self.items.retain(|s| s.until > now);
+        self.items.clone()
+    }
+}
+
+// If the checker is silenced by any of the windows or silences, returns whether a summary
+// is requested when they end.
+pub fn silencing(
+    checker: &CheckerConfig,
+    windows: &[MaintenanceWindow],
+    silences: &[Silence],
+    now: SystemTime,
+) -> Option<bool> {
+    let windows = windows
+        .iter()
+        .filter(|w| w.scope.covers(checker) && is_active(w, now))
+        .map(|w| w.summary);
+    let silences = silences
+        .iter()
+        .filter(|s| s.scope.covers(checker))
+        .map(|s| s.summary);
+    windows
+        .chain(silences)
+        .fold(None, |acc, summary| Some(acc.unwrap_or(false) || summary))
+}
+
+pub fn is_active(window: &MaintenanceWindow, now: SystemTime) -> bool {
+    match window.schedule {
+        MaintenanceSchedule::Range { start, end } => start <= now && now < end,
+        MaintenanceSchedule::Cron { ref cron, duration } => {
+            let now = now
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            // The window is active, if it has started within the last `duration`
+            let since = (now + 1).saturating_sub(duration.as_secs());
+            cron.last_match(now, since).is_some()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_active() {
+        // 2019-02-05T03:00:00Z
+        let tuesday_3am = UNIX_EPOCH + Duration::from_secs(1_549_335_600);
+        let window = MaintenanceWindow {
+            id: "backup".to_owned(),
+            schedule: MaintenanceSchedule::Cron {
+                cron: Cron::parse("0 3 * * *").unwrap(),
+                duration: Duration::from_secs(30 * 60),
+            },
+            scope: MaintenanceScope::default(),
+            summary: true,
+        };
+        assert!(is_active(&window, tuesday_3am));
+        assert!(is_active(
+            &window,
+            tuesday_3am + Duration::from_secs(29 * 60)
+        ));
+        assert!(!is_active(
+            &window,
+            tuesday_3am + Duration::from_secs(30 * 60)
+        ));
+        assert!(!is_active(&window, tuesday_3am - Duration::from_secs(1)));
+    }
+}
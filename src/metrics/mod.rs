@@ -0,0 +1,357 @@
+// Collects metrics of checkers and notifiers and renders them in Prometheus text format.
+// The watcher records results of every check, the reactor records confirmed states and
+// deliveries of notifications.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::reactor::State;
+
+mod server;
+
+pub use self::server::spawn_server;
+
+// Upper bounds (in seconds) of the response time histogram buckets.
+const RESPONSE_TIME_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    // BTreeMap keeps the output in a stable order
+    checkers: BTreeMap<String, CheckerMetrics>,
+    notifiers: BTreeMap<String, NotifierMetrics>,
+}
+
+#[derive(Debug, Default)]
+struct CheckerMetrics {
+    state: Option<State>,
+    http_status: Option<u16>,
+    consecutive_failures: u64,
+    response_time: Histogram,
+}
+
+#[derive(Debug, Default)]
+struct NotifierMetrics {
+    successes: u64,
+    failures: u64,
+}
+
+#[derive(Debug)]
+struct Histogram {
+    // Cumulative counts, one per bucket of RESPONSE_TIME_BUCKETS
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; RESPONSE_TIME_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(RESPONSE_TIME_BUCKETS.iter()) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+impl Metrics {
+    // Records a result of a single check (before thresholds are applied).
+    pub fn record_check(
+        &self,
+        checker_id: &str,
+        state: &State,
+        http_status: Option<u16>,
+        response_time: Option<Duration>,
+    ) {
+        let mut registry = self.registry.lock().unwrap();
+        let checker = registry.checkers.entry(checker_id.to_owned()).or_default();
+
+        checker.http_status = http_status;
+        match state {
+            State::Up | State::Degraded => checker.consecutive_failures = 0,
+            State::Down => checker.consecutive_failures += 1,
+        }
+        // Response time makes sense only if a response was received
+        if let Some(response_time) = response_time {
+            checker
+                .response_time
+                .observe(duration_to_secs(response_time));
+        }
+    }
+
+    // Records a confirmed state of a checker.
+    pub fn set_state(&self, checker_id: &str, state: &State) {
+        let mut registry = self.registry.lock().unwrap();
+        let checker = registry.checkers.entry(checker_id.to_owned()).or_default();
+        checker.state = Some(state.clone());
+    }
+
+    pub fn record_notification(&self, notifier_id: &str, success: bool) {
+        let mut registry = self.registry.lock().unwrap();
+        let notifier = registry
+            .notifiers
+            .entry(notifier_id.to_owned())
+            .or_default();
+        if success {
+            notifier.successes += 1;
+        } else {
+            notifier.failures += 1;
+        }
+    }
+
+    // Forgets checkers and notifiers, that are no longer declared in the config.
+    pub fn retain(&self, checker_ids: &[&str], notifier_ids: &[&str]) {
+        let mut registry = self.registry.lock().unwrap();
+        registry
+            .checkers
+            .retain(|id, _| checker_ids.contains(&id.as_str()));
+        registry
+            .notifiers
+            .retain(|id, _| notifier_ids.contains(&id.as_str()));
+    }
+
+    pub fn render(&self) -> String {
+        let registry = self.registry.lock().unwrap();
+        let mut out = String::new();
+
+        // Writing into a String never fails, so the results of writeln! are ignored
+        let _ = writeln!(
+            out,
+            "# HELP hellcheck_checker_up Whether the service is up or degraded (1) or down (0), after thresholds are applied.\n\
+             # TYPE hellcheck_checker_up gauge"
+        );
+        for (id, checker) in registry.checkers.iter() {
+            if let Some(ref state) = checker.state {
+                let _ = writeln!(
+                    out,
+                    "hellcheck_checker_up{{checker=\"{}\"}} {}",
+                    escape(id),
+                    (*state != State::Down) as u8
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP hellcheck_checker_degraded Whether the service responds slower than `warn_latency` (1) or not (0).\n\
+             # TYPE hellcheck_checker_degraded gauge"
+        );
+        for (id, checker) in registry.checkers.iter() {
+            if let Some(ref state) = checker.state {
+                let _ = writeln!(
+                    out,
+                    "hellcheck_checker_degraded{{checker=\"{}\"}} {}",
+                    escape(id),
+                    (*state == State::Degraded) as u8
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP hellcheck_checker_http_status HTTP status of the last response, 0 if no response was received.\n\
+             # TYPE hellcheck_checker_http_status gauge"
+        );
+        for (id, checker) in registry.checkers.iter() {
+            let _ = writeln!(
+                out,
+                "hellcheck_checker_http_status{{checker=\"{}\"}} {}",
+                escape(id),
+                checker.http_status.unwrap_or(0)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP hellcheck_checker_consecutive_failures Number of consecutive failed checks.\n\
+             # TYPE hellcheck_checker_consecutive_failures gauge"
+        );
+        for (id, checker) in registry.checkers.iter() {
+            let _ = writeln!(
+                out,
+                "hellcheck_checker_consecutive_failures{{checker=\"{}\"}} {}",
+                escape(id),
+                checker.consecutive_failures
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP hellcheck_checker_response_time_seconds Response time of the checked service.\n\
+             # TYPE hellcheck_checker_response_time_seconds histogram"
+        );
+        for (id, checker) in registry.checkers.iter() {
+            let id = escape(id);
+            let histogram = &checker.response_time;
+            for (bound, count) in RESPONSE_TIME_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                let _ = writeln!(
+                    out,
+                    "hellcheck_checker_response_time_seconds_bucket{{checker=\"{}\",le=\"{}\"}} {}",
+                    id, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "hellcheck_checker_response_time_seconds_bucket{{checker=\"{}\",le=\"+Inf\"}} {}\n\
+                 hellcheck_checker_response_time_seconds_sum{{checker=\"{}\"}} {}\n\
+                 hellcheck_checker_response_time_seconds_count{{checker=\"{}\"}} {}",
+                id, histogram.count, id, histogram.sum, id, histogram.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP hellcheck_notifications_total Number of notifications sent by the notifier.\n\
+             # TYPE hellcheck_notifications_total counter"
+        );
+        for (id, notifier) in registry.notifiers.iter() {
+            let id = escape(id);
+            let _ = writeln!(
+                out,
+                "hellcheck_notifications_total{{notifier=\"{}\",result=\"success\"}} {}\n\
+                 hellcheck_notifications_total{{notifier=\"{}\",result=\"failure\"}} {}",
+                id, notifier.successes, id, notifier.failures
+            );
+        }
+
+        out
+    }
+}
+
+fn duration_to_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+// Escapes a label value according to Prometheus text format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_tcp_check() {
+        let metrics = Metrics::default();
+        metrics.record_check("db", &State::Up, None, Some(Duration::from_millis(30)));
+        // Refused connection has no response time
+        metrics.record_check("db", &State::Down, None, None);
+        let out = metrics.render();
+
+        let expected = [
+            "hellcheck_checker_http_status{checker=\"db\"} 0",
+            "hellcheck_checker_consecutive_failures{checker=\"db\"} 1",
+            "hellcheck_checker_response_time_seconds_bucket{checker=\"db\",le=\"0.025\"} 0",
+            "hellcheck_checker_response_time_seconds_bucket{checker=\"db\",le=\"0.05\"} 1",
+            "hellcheck_checker_response_time_seconds_bucket{checker=\"db\",le=\"+Inf\"} 1",
+            "hellcheck_checker_response_time_seconds_sum{checker=\"db\"} 0.03",
+            "hellcheck_checker_response_time_seconds_count{checker=\"db\"} 1",
+        ];
+        for line in expected.iter() {
+            assert!(
+                out.lines().any(|l| l == *line),
+                "{} is missing in:\n{}",
+                line,
+                out
+            );
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_check(
+            "web",
+            &State::Up,
+            Some(200),
+            Some(Duration::from_millis(200)),
+        );
+        metrics.record_check(
+            "web",
+            &State::Degraded,
+            Some(200),
+            Some(Duration::from_secs(3)),
+        );
+        metrics.set_state("web", &State::Degraded);
+        metrics.record_notification("ops", true);
+        metrics.record_notification("ops", true);
+        metrics.record_notification("ops", false);
+
+        let expected = r#"# HELP hellcheck_checker_up Whether the service is up or degraded (1) or down (0), after thresholds are applied.
+# TYPE hellcheck_checker_up gauge
+hellcheck_checker_up{checker="web"} 1
+# HELP hellcheck_checker_degraded Whether the service responds slower than `warn_latency` (1) or not (0).
+# TYPE hellcheck_checker_degraded gauge
+hellcheck_checker_degraded{checker="web"} 1
+# HELP hellcheck_checker_http_status HTTP status of the last response, 0 if no response was received.
+# TYPE hellcheck_checker_http_status gauge
+hellcheck_checker_http_status{checker="web"} 200
+# HELP hellcheck_checker_consecutive_failures Number of consecutive failed checks.
+# TYPE hellcheck_checker_consecutive_failures gauge
+hellcheck_checker_consecutive_failures{checker="web"} 0
+# HELP hellcheck_checker_response_time_seconds Response time of the checked service.
+# TYPE hellcheck_checker_response_time_seconds histogram
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="0.01"} 0
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="0.025"} 0
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="0.05"} 0
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="0.1"} 0
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="0.25"} 1
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="0.5"} 1
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="1"} 1
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="2.5"} 1
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="5"} 2
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="10"} 2
+hellcheck_checker_response_time_seconds_bucket{checker="web",le="+Inf"} 2
+hellcheck_checker_response_time_seconds_sum{checker="web"} 3.2
+hellcheck_checker_response_time_seconds_count{checker="web"} 2
+# HELP hellcheck_notifications_total Number of notifications sent by the notifier.
+# TYPE hellcheck_notifications_total counter
+hellcheck_notifications_total{notifier="ops",result="success"} 2
+hellcheck_notifications_total{notifier="ops",result="failure"} 1
+"#;
+        assert_eq!(metrics.render(), expected);
+    }
+
+    #[test]
+    fn test_render_escaped_labels() {
+        let metrics = Metrics::default();
+        metrics.set_state("a\\b \"c\"\nd", &State::Down);
+        metrics.record_notification("ops\n", false);
+        let out = metrics.render();
+
+        let expected = [
+            r#"hellcheck_checker_up{checker="a\\b \"c\"\nd"} 0"#,
+            r#"hellcheck_notifications_total{notifier="ops\n",result="failure"} 1"#,
+        ];
+        for line in expected.iter() {
+            assert!(
+                out.lines().any(|l| l == *line),
+                "{} is missing in:\n{}",
+                line,
+                out
+            );
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use hyper::rt::Future;
+use hyper::service::service_fn_ok;
+use hyper::{Body, Request, Response, Server, StatusCode};
+
+use super::Metrics;
+use crate::config::MetricsConfig;
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+// Starts HTTP server, that exposes metrics at the configured path, in a separate thread.
+pub fn spawn_server(config: &MetricsConfig, metrics: Metrics) {
+    let path = config.path.clone();
+    let builder = match Server::try_bind(&config.listen) {
+        Ok(builder) => builder,
+        Err(err) => {
+            eprintln!(
+                "ERROR: Failed to start metrics server on {}: {}",
+                config.listen, err
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let server = builder
+        .serve(move || {
+            let path = path.clone();
+            let metrics = metrics.clone();
+            service_fn_ok(move |req: Request<Body>| respond(&req, &path, &metrics))
+        })
+        .map_err(|err| eprintln!("ERROR: Metrics server failed: {}", err));
+
+    info!("Serving metrics on http://{}{}", config.listen, config.path);
+    std::thread::spawn(move || hyper::rt::run(server));
+}
+
+fn respond(req: &Request<Body>, path: &str, metrics: &Metrics) -> Response<Body> {
+    if req.uri().path() != path {
+        let mut resp = Response::new(Body::from("Not Found"));
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        return resp;
+    }
+
+    let mut resp = Response::new(Body::from(metrics.render()));
+    resp.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(CONTENT_TYPE),
+    );
+    resp
+}
@@ -1,7 +1,7 @@
 use std::process::Command;
 
 use crate::config::CommandNotifierConfig;
-use crate::notifiers::{Notification, Notifier};
+use crate::notifiers::{format_timestamp, Notification, Notifier};
 use crate::reactor::State;
 
 pub struct CommandNotifier {
@@ -20,16 +20,55 @@ impl CommandNotifier {
 
 impl Notifier for CommandNotifier {
     fn notify(&self, notification: &Notification) -> Result<(), ()> {
+        // Degraded service still responds
         let ok = match notification.state {
-            State::Up => "true".to_owned(),
+            State::Up | State::Degraded => "true".to_owned(),
             State::Down => "false".to_owned(),
         };
 
+        let reason = notification
+            .reason
+            .as_ref()
+            .map(|r| r.to_string())
+            .unwrap_or_default();
+        let status = notification
+            .http_status
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+        let response_time = notification
+            .response_time
+            .map(|t| t.as_millis().to_string())
+            .unwrap_or_default();
+        let outage_duration = notification
+            .outage_duration
+            .map(|d| d.as_secs().to_string())
+            .unwrap_or_default();
+        let certificate_days_left = notification
+            .certificate_days_left
+            .map(|d| d.to_string())
+            .unwrap_or_default();
+
         let res = Command::new(&self.command)
             .args(self.arguments.iter())
             .env("HELLCHECK_ID", notification.checker_id.clone())
             .env("HELLCHECK_URL", notification.checker_url.clone())
             .env("HELLCHECK_OK", ok)
+            .env("HELLCHECK_STATE", notification.state.to_string())
+            .env("HELLCHECK_REASON", reason)
+            .env("HELLCHECK_STATUS", status)
+            .env("HELLCHECK_RESPONSE_TIME", response_time)
+            .env("HELLCHECK_CERT_DAYS_LEFT", certificate_days_left)
+            .env("HELLCHECK_REMINDER", notification.reminder.to_string())
+            .env(
+                "HELLCHECK_AFTER_MAINTENANCE",
+                notification.after_maintenance.to_string(),
+            )
+            .env("HELLCHECK_OUTAGE_DURATION", outage_duration)
+            .env("HELLCHECK_AFFECTED", notification.affected.join(","))
+            .env(
+                "HELLCHECK_TIMESTAMP",
+                format_timestamp(notification.timestamp),
+            )
             .status();
 
         match res {
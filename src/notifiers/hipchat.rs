@@ -33,26 +33,42 @@ impl Notifier for HipchatNotifier {
         );
 
         let res = self.http_client.post(&url).json(&payload).send();
-        res.map(|_| ()).map_err(|_| ())
+        res.and_then(|resp| resp.error_for_status())
+            .map(|_| ())
+            .map_err(|_| ())
     }
 }
 
 fn build_payload(notification: &Notification) -> HashMap<&'static str, String> {
     let color = match notification.state {
         State::Up => "green".to_owned(),
+        State::Degraded => "yellow".to_owned(),
         State::Down => "red".to_owned(),
     };
 
-    let message = match notification.state {
+    let mut message = match notification.state {
         State::Up => format!(
-            "{} is up (dealwithit)\n{}",
-            notification.checker_id, notification.checker_url
+            "{} is {} (dealwithit)\n{}",
+            notification.checker_id,
+            notification.state_text(),
+            notification.checker_url
+        ),
+        State::Degraded => format!(
+            "{} is {} (sadpanda)\n{}",
+            notification.checker_id,
+            notification.state_text(),
+            notification.checker_url
         ),
         State::Down => format!(
-            "{} is down (boom)\n{}",
-            notification.checker_id, notification.checker_url
+            "{} is {} (boom)\n{}",
+            notification.checker_id,
+            notification.state_text(),
+            notification.checker_url
         ),
     };
+    for (title, value) in notification.details() {
+        message.push_str(&format!("\n{}: {}", title, value));
+    }
 
     let mut payload = HashMap::new();
     payload.insert("color", color);
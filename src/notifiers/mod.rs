@@ -1,4 +1,6 @@
-use crate::reactor::State;
+use std::time::{Duration, SystemTime};
+
+use crate::reactor::{Reason, State};
 
 mod telegram;
 pub use self::telegram::TelegramNotifier;
@@ -17,6 +19,70 @@ pub struct Notification {
     pub checker_id: String,
     pub checker_url: String,
     pub state: State,
+    pub reason: Option<Reason>,
+    pub http_status: Option<u16>,
+    pub response_time: Option<Duration>,
+    pub certificate_days_left: Option<i64>,
+    // How long the service has been down, set for reminders and recoveries
+    pub outage_duration: Option<Duration>,
+    // Set if the notification repeats, that the service is still down
+    pub reminder: bool,
+    // Set if the notification summarizes the state after a maintenance window or a silence
+    pub after_maintenance: bool,
+    // Ids of the checkers, that are down because of this one (their outages are not notified)
+    pub affected: Vec<String>,
+    pub timestamp: SystemTime,
+}
+
+impl Notification {
+    // State as it's written in the messages, e.g. "still down" for reminders.
+    pub fn state_text(&self) -> String {
+        if self.reminder {
+            format!("still {}", self.state)
+        } else if self.after_maintenance {
+            format!("{} after maintenance", self.state)
+        } else {
+            self.state.to_string()
+        }
+    }
+
+    // Human readable diagnostics as (title, value) pairs, that notifiers attach to messages.
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = vec![];
+        if let Some(ref reason) = self.reason {
+            details.push(("Reason", reason.to_string()));
+        }
+        if let Some(status) = self.http_status {
+            details.push(("HTTP status", status.to_string()));
+        }
+        if let Some(response_time) = self.response_time {
+            details.push(("Response time", format_duration(response_time)));
+        }
+        if let Some(outage_duration) = self.outage_duration {
+            details.push(("Down for", format_outage_duration(outage_duration)));
+        }
+        if let Some(days_left) = self.certificate_days_left {
+            details.push(("Certificate expires in", format!("{} days", days_left)));
+        }
+        if !self.affected.is_empty() {
+            details.push(("Also down because of it", self.affected.join(", ")));
+        }
+        details.push(("Time", format_timestamp(self.timestamp)));
+        details
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{}ms", duration.as_millis())
+}
+
+// Outage duration is rounded to seconds, e.g. "1h 5m 12s".
+pub fn format_outage_duration(duration: Duration) -> String {
+    humantime::format_duration(Duration::from_secs(duration.as_secs())).to_string()
+}
+
+pub fn format_timestamp(timestamp: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(timestamp).to_string()
 }
 
 pub trait Notifier {
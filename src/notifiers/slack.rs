@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::time::UNIX_EPOCH;
 
 use hyper::Uri;
+use serde_json::{json, Value as JsonValue};
 
 use crate::config::SlackNotifierConfig;
 use crate::notifiers::{Notification, Notifier};
@@ -25,43 +26,81 @@ impl Notifier for SlackNotifier {
         let payload = build_payload(notification);
         let url = format!("{}", self.webhook_url);
         let res = self.http_client.post(&url).json(&payload).send();
-        res.map(|_| ()).map_err(|_| ())
+        res.and_then(|resp| resp.error_for_status())
+            .map(|_| ())
+            .map_err(|_| ())
     }
 }
 
-fn build_payload(
-    notification: &Notification,
-) -> HashMap<&'static str, Vec<HashMap<&'static str, String>>> {
+fn build_payload(notification: &Notification) -> JsonValue {
     let fallback = match notification.state {
         State::Up => format!(
-            "{} is up :thumbsup:\n{}",
-            notification.checker_id, notification.checker_url
+            "{} is {} :thumbsup:\n{}",
+            notification.checker_id,
+            notification.state_text(),
+            notification.checker_url
+        ),
+        State::Degraded => format!(
+            "{} is {} :snail:\n{}",
+            notification.checker_id,
+            notification.state_text(),
+            notification.checker_url
         ),
         State::Down => format!(
-            "{} is down :fire:\n{}",
-            notification.checker_id, notification.checker_url
+            "{} is {} :fire:\n{}",
+            notification.checker_id,
+            notification.state_text(),
+            notification.checker_url
         ),
     };
     let color = match notification.state {
         State::Up => "good".to_owned(),
+        State::Degraded => "warning".to_owned(),
         State::Down => "danger".to_owned(),
     };
     let title = match notification.state {
-        State::Up => format!("{} is up :thumbsup:", notification.checker_id),
-        State::Down => format!("{} is down :fire:", notification.checker_id),
+        State::Up => format!(
+            "{} is {} :thumbsup:",
+            notification.checker_id,
+            notification.state_text()
+        ),
+        State::Degraded => format!(
+            "{} is {} :snail:",
+            notification.checker_id,
+            notification.state_text()
+        ),
+        State::Down => format!(
+            "{} is {} :fire:",
+            notification.checker_id,
+            notification.state_text()
+        ),
     };
     let title_link = notification.checker_url.clone();
 
-    let mut attachment = HashMap::new();
-    attachment.insert("fallback", fallback);
-    attachment.insert("color", color);
-    attachment.insert("title", title);
-    attachment.insert("title_link", title_link);
-
-    let attachments = vec![attachment];
+    let fields: Vec<JsonValue> = notification
+        .details()
+        .into_iter()
+        .map(|(title, value)| {
+            // Long values like failure reason deserve a full row
+            let short = value.len() < 40;
+            json!({ "title": title, "value": value, "short": short })
+        })
+        .collect();
 
-    let mut payload = HashMap::new();
-    payload.insert("attachments", attachments);
+    let timestamp = notification
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
 
-    payload
+    json!({
+        "attachments": [{
+            "fallback": fallback,
+            "color": color,
+            "title": title,
+            "title_link": title_link,
+            "fields": fields,
+            "ts": timestamp,
+        }]
+    })
 }
@@ -22,22 +22,42 @@ impl TelegramNotifier {
 
 impl Notifier for TelegramNotifier {
     fn notify(&self, notification: &Notification) -> Result<(), ()> {
-        let text = match notification.state {
+        let mut text = match notification.state {
             State::Up => {
                 let emoji_baloon = '\u{1F388}';
                 format!(
-                    "{} is up {}\n{}",
-                    notification.checker_id, emoji_baloon, notification.checker_url
+                    "{} is {} {}\n{}",
+                    notification.checker_id,
+                    notification.state_text(),
+                    emoji_baloon,
+                    notification.checker_url
+                )
+            }
+            State::Degraded => {
+                let emoji_snail = '\u{1F40C}';
+                format!(
+                    "{} is {} {}\n{}",
+                    notification.checker_id,
+                    notification.state_text(),
+                    emoji_snail,
+                    notification.checker_url
                 )
             }
             State::Down => {
                 let emoji_fire = '\u{1F525}';
                 format!(
-                    "{} is down {}\n{}",
-                    notification.checker_id, emoji_fire, notification.checker_url
+                    "{} is {} {}\n{}",
+                    notification.checker_id,
+                    notification.state_text(),
+                    emoji_fire,
+                    notification.checker_url
                 )
             }
         };
+        for (title, value) in notification.details() {
+            text.push_str(&format!("\n{}: {}", title, value));
+        }
+
         let mut payload = HashMap::new();
         payload.insert("chat_id", self.chat_id.clone());
         payload.insert("text", text);
@@ -45,6 +65,8 @@ impl Notifier for TelegramNotifier {
         let url = format!("https://api.telegram.org/bot{}/sendMessage", self.token);
 
         let res = self.http_client.post(&url).json(&payload).send();
-        res.map(|_| ()).map_err(|_| ())
+        res.and_then(|resp| resp.error_for_status())
+            .map(|_| ())
+            .map_err(|_| ())
     }
 }
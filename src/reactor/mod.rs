@@ -0,0 +1,1780 @@
+use serde_derive::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::config::{CheckerConfig, CheckerKind, EscalationStep, FileConfig, NotifierConfig};
+use crate::maintenance::{self, SharedSilences};
+use crate::metrics::Metrics;
+use crate::notifiers::Notifier as NotifierTrait;
+use crate::notifiers::{
+    CommandNotifier, HipchatNotifier, Notification, SlackNotifier, TelegramNotifier,
+};
+
+mod persistence;
+
+// How often deadlines of heartbeat checkers, reminders, escalations and maintenance windows
+// are verified.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    Up,
+    // Responds, but slower than `warn_latency`
+    Degraded,
+    Down,
+}
+
+impl State {
+    // Severity of the state, used to tell a recovery from a deterioration.
+    fn severity(&self) -> u8 {
+        match self {
+            State::Up => 0,
+            State::Degraded => 1,
+            State::Down => 2,
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            State::Up => "up",
+            State::Degraded => "degraded",
+            State::Down => "down",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Explains why a check has failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Reason {
+    UnexpectedStatus(u16),
+    Timeout(Duration),
+    Dns(String),
+    Connect(String),
+    Tls(String),
+    Auth(String),
+    Assertion(String),
+    Redirect(String),
+    // Response time exceeded the limit
+    Latency(Duration, Duration),
+    // No ping was received by a heartbeat checker within the given time
+    MissedPing(Duration),
+    Other(String),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::UnexpectedStatus(status) => {
+                let canonical_reason = hyper::StatusCode::from_u16(*status)
+                    .ok()
+                    .and_then(|s| s.canonical_reason());
+                match canonical_reason {
+                    Some(text) => write!(f, "Unexpected HTTP status {} {}", status, text),
+                    None => write!(f, "Unexpected HTTP status {}", status),
+                }
+            }
+            Reason::Timeout(timeout) => {
+                write!(
+                    f,
+                    "Timed out after {}",
+                    humantime::format_duration(*timeout)
+                )
+            }
+            Reason::Dns(err) => write!(f, "DNS resolution failed: {}", err),
+            Reason::Connect(err) => write!(f, "Connection failed: {}", err),
+            Reason::Tls(err) => write!(f, "TLS error: {}", err),
+            Reason::Auth(message) => write!(f, "{}", message),
+            Reason::Assertion(message) => write!(f, "{}", message),
+            Reason::Redirect(message) => write!(f, "{}", message),
+            Reason::Latency(response_time, limit) => write!(
+                f,
+                "Response time {}ms exceeds {}",
+                response_time.as_millis(),
+                humantime::format_duration(*limit)
+            ),
+            Reason::MissedPing(duration) => write!(
+                f,
+                "No ping received within {}",
+                humantime::format_duration(*duration)
+            ),
+            Reason::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StateMessage {
+    pub checker_id: String,
+    pub state: State,
+    pub reason: Option<Reason>,
+    pub http_status: Option<u16>,
+    // Not set if the service has not responded
+    pub response_time: Option<Duration>,
+    pub certificate_days_left: Option<i64>,
+    pub timestamp: SystemTime,
+}
+
+// States of the checkers by their ids. Shared with the status server.
+pub type SharedStates = Arc<Mutex<HashMap<String, CheckerState>>>;
+
+// Last confirmed state of a checker and number of consecutive results, that contradict it.
+#[derive(Debug, Clone)]
+pub struct CheckerState {
+    pub state: State,
+    pub mismatches: u32,
+    pub changed_at: Option<SystemTime>,
+    pub url: String,
+    pub last_checked_at: Option<SystemTime>,
+    // Reason of the most recent failed check
+    pub last_error: Option<Reason>,
+    // Heartbeat checkers go down, if the next ping is not received by this time
+    pub ping_deadline: Option<SystemTime>,
+    // Dependency, whose outage has caused the outage of this checker. Such outages are not notified
+    pub root_cause: Option<String>,
+}
+
+impl CheckerState {
+    // Registers a new check result and returns true if the confirmed state has changed.
+    fn update(&mut self, checker: &CheckerConfig, msg: &StateMessage) -> bool {
+        self.last_checked_at = Some(msg.timestamp);
+        if msg.state != State::Up {
+            self.last_error = msg.reason.clone();
+        }
+
+        if msg.state == self.state {
+            self.mismatches = 0;
+            return false;
+        }
+
+        self.mismatches += 1;
+        // Recovery (e.g. from down to degraded) is confirmed by success_threshold
+        let threshold = if msg.state.severity() < self.state.severity() {
+            checker.success_threshold
+        } else {
+            checker.fail_threshold
+        };
+
+        if self.mismatches >= threshold {
+            self.state = msg.state.clone();
+            self.mismatches = 0;
+            self.changed_at = Some(msg.timestamp);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// Outage, that is being escalated. Dropping it cancels the pending steps.
+struct Escalation {
+    down_since: SystemTime,
+    // Steps, that are not notified yet
+    pending: Vec<EscalationStep>,
+    // Notifiers of the notified steps, they are notified about the recovery as well
+    notified: Vec<String>,
+}
+
+impl Escalation {
+    // Steps, that are due before `now`, are considered as notified.
+    fn new(checker: &CheckerConfig, down_since: SystemTime, now: SystemTime) -> Self {
+        let (notified_steps, pending): (Vec<EscalationStep>, Vec<EscalationStep>) = checker
+            .escalation
+            .iter()
+            .cloned()
+            .partition(|step| down_since + step.after < now);
+        Self {
+            down_since,
+            pending,
+            notified: notified_steps
+                .into_iter()
+                .flat_map(|s| s.notifiers)
+                .collect(),
+        }
+    }
+}
+
+// Checker, whose notifications are suppressed by a maintenance window or a silence.
+struct Silenced {
+    // State when the maintenance has started
+    state_before: State,
+    // Notify about the state when the maintenance ends, if it has changed
+    summary: bool,
+}
+
+pub enum Message {
+    // Result of a check, sent by the watcher (or by the server, when a ping is received)
+    State(StateMessage),
+    // New configuration, sent when the config file is reloaded
+    Reload(FileConfig),
+}
+
+pub fn spawn(
+    receiver: mpsc::Receiver<Message>,
+    config: FileConfig,
+    state_file: Option<PathBuf>,
+    metrics: Metrics,
+    silences: SharedSilences,
+) -> SharedStates {
+    let states = SharedStates::default();
+    let reactor_states = Arc::clone(&states);
+
+    ::std::thread::spawn(move || {
+        let mut reactor = Reactor::new(config, state_file, metrics, reactor_states, silences);
+
+        loop {
+            match receiver.recv_timeout(TICK_INTERVAL) {
+                Ok(Message::State(msg)) => reactor.handle_state_message(msg),
+                Ok(Message::Reload(config)) => reactor.reload(config),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            reactor.tick();
+        }
+    });
+
+    states
+}
+
+struct Reactor {
+    config: FileConfig,
+    states: SharedStates,
+    notifiers: HashMap<String, Box<dyn NotifierTrait>>,
+    state_file: Option<PathBuf>,
+    metrics: Metrics,
+    // When the last reminder was sent, by checker and notifier ids
+    reminded_at: HashMap<(String, String), SystemTime>,
+    // Escalations of the ongoing outages by checker ids
+    escalations: HashMap<String, Escalation>,
+    // Silences created at runtime, shared with the server
+    silences: SharedSilences,
+    // Checkers under maintenance by their ids
+    silenced: HashMap<String, Silenced>,
+}
+
+impl Reactor {
+    fn new(
+        config: FileConfig,
+        state_file: Option<PathBuf>,
+        metrics: Metrics,
+        shared_states: SharedStates,
+        silences: SharedSilences,
+    ) -> Self {
+        let mut states = match state_file {
+            Some(ref path) => persistence::load(path).unwrap_or_else(|err| {
+                eprintln!("WARNING: {}. All checkers are assumed to be up.", err);
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+        states.retain(|id, _| config.checkers.iter().any(|c| c.id == *id));
+        init_states(&config, &mut states);
+        let notifiers = build_notifiers(&config);
+        *shared_states.lock().unwrap() = states;
+
+        let mut reactor = Self {
+            config,
+            states: shared_states,
+            notifiers,
+            state_file,
+            metrics,
+            reminded_at: HashMap::new(),
+            escalations: HashMap::new(),
+            silences,
+            silenced: HashMap::new(),
+        };
+        reactor.restore_escalations();
+        reactor.restore_reminders();
+        reactor.update_metrics();
+        reactor
+    }
+
+    fn handle_state_message(&mut self, msg: StateMessage) {
+        // A message may come from a checker, that was removed from the config by a reload
+        let checker = match self.config.get_checker_by_id(&msg.checker_id) {
+            Some(checker) => checker,
+            None => return,
+        };
+        // The state before a maintenance must be known before the message changes it
+        self.update_silences();
+
+        // The lock is released before notifiers are called, so the status server is not blocked
+        let (changed, prev_state, prev_changed_at, confirmed_state) = {
+            let mut states = self.states.lock().unwrap();
+            // unwrap is safe here, because `states` was initialized with all possible checker ids.
+            let checker_state = states.get_mut(&msg.checker_id).unwrap();
+            let prev = (checker_state.state.clone(), checker_state.mismatches);
+            let prev_state = checker_state.state.clone();
+            let prev_changed_at = checker_state.changed_at;
+            let changed = checker_state.update(&checker, &msg);
+            checker_state.ping_deadline = ping_deadline(&checker, msg.timestamp);
+            let confirmed_state = checker_state.state.clone();
+            if (confirmed_state.clone(), checker_state.mismatches) != prev {
+                self.persist_states(&states);
+            }
+            (changed, prev_state, prev_changed_at, confirmed_state)
+        };
+
+        if !changed {
+            return;
+        }
+        self.metrics.set_state(&checker.id, &confirmed_state);
+
+        // Any change of the state ends the outage, so the pending escalation steps are cancelled.
+        // Notifiers, that have been involved, are notified about the recovery as well.
+        let mut notifier_ids = checker.notifiers.clone();
+        if let Some(escalation) = self.escalations.remove(&checker.id) {
+            notifier_ids.extend(escalation.notified);
+        }
+        let (root_cause, prev_root_cause) = self.update_root_cause(&checker, &confirmed_state);
+
+        if self.silenced.contains_key(&checker.id) {
+            info!(
+                "Notification about {} is suppressed by maintenance",
+                checker.id
+            );
+            return;
+        }
+        // The outage of the dependency has been notified already
+        if let Some(root_cause) = root_cause {
+            info!(
+                "Notification about {} is suppressed, because {} is down",
+                checker.id, root_cause
+            );
+            return;
+        }
+        // Nobody was told about the outage, so nobody needs to be told about the recovery
+        if let Some(root_cause) = prev_root_cause {
+            info!(
+                "Notification about {} is suppressed, because its outage was caused by {}",
+                checker.id, root_cause
+            );
+            return;
+        }
+
+        // Send a message if the checker is interested in the transition
+        if checker.notifies(&prev_state, &confirmed_state) {
+            if confirmed_state == State::Down && !checker.escalation.is_empty() {
+                let escalation = Escalation::new(&checker, msg.timestamp, msg.timestamp);
+                self.escalations.insert(checker.id.clone(), escalation);
+            }
+
+            let mut notification = build_notification(&checker, &msg);
+            if confirmed_state == State::Down {
+                notification.affected = self.affected_by(&checker.id);
+            }
+            // Recovery tells how long the outage has lasted
+            if prev_state == State::Down {
+                notification.outage_duration =
+                    prev_changed_at.and_then(|t| msg.timestamp.duration_since(t).ok());
+            }
+            for notifier_id in unique(&notifier_ids) {
+                self.send_notification(notifier_id, &notification);
+            }
+        }
+    }
+
+    fn send_notification(&self, notifier_id: &str, notification: &Notification) {
+        // Notifiers of an escalation may have been removed from the config by a reload
+        let notifier = match self.notifiers.get(notifier_id) {
+            Some(notifier) => notifier,
+            None => {
+                eprintln!(
+                    "ERROR: Unknown notifier `{}` can not notify that {} is {}",
+                    notifier_id,
+                    notification.checker_id,
+                    notification.state_text()
+                );
+                return;
+            }
+        };
+        info!("Sending a notification to {}", notifier_id);
+        let res = notifier.notify(notification);
+        self.metrics.record_notification(notifier_id, res.is_ok());
+        if res.is_err() {
+            eprintln!(
+                "ERROR: Notifier `{}` failed to notify that {} is {}",
+                notifier_id,
+                notification.checker_id,
+                notification.state_text()
+            );
+        }
+    }
+
+    // Finds out whether the new state of the checker is caused by a dependency, that is down.
+    // Returns the new and the previous root causes.
+    fn update_root_cause(
+        &self,
+        checker: &CheckerConfig,
+        state: &State,
+    ) -> (Option<String>, Option<String>) {
+        let mut states = self.states.lock().unwrap();
+        let root_cause = find_root_cause(checker, state, &states);
+        // unwrap is safe here, because `states` was initialized with all possible checker ids.
+        let checker_state = states.get_mut(&checker.id).unwrap();
+        let prev_root_cause = std::mem::replace(&mut checker_state.root_cause, root_cause.clone());
+        (root_cause, prev_root_cause)
+    }
+
+    // Checkers, whose outages are caused by the given one. Their outages are not notified,
+    // so the notifications about the root cause list them instead.
+    fn affected_by(&self, checker_id: &str) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .states
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, s)| s.root_cause.as_deref() == Some(checker_id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    // Outages, that were caused by dependencies, which have recovered since, are notified now.
+    fn update_dependents(&mut self) {
+        let mut revealed: Vec<CheckerConfig> = vec![];
+        {
+            let mut states = self.states.lock().unwrap();
+            // Root causes may be chained, so they are updated until nothing changes
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for checker in self.config.checkers.iter() {
+                    let root_cause = match states.get(&checker.id) {
+                        Some(s) if s.root_cause.is_some() => {
+                            find_root_cause(checker, &s.state, &states)
+                        }
+                        _ => continue,
+                    };
+                    // unwrap is safe here, because the state was found above
+                    let checker_state = states.get_mut(&checker.id).unwrap();
+                    if checker_state.root_cause != root_cause {
+                        changed = true;
+                        if root_cause.is_none() {
+                            revealed.push(checker.clone());
+                        }
+                        checker_state.root_cause = root_cause;
+                    }
+                }
+            }
+        }
+
+        for checker in revealed {
+            self.notify_revealed_outage(&checker);
+        }
+    }
+
+    // Notifies about an outage, that was suppressed, as if it has just started.
+    fn notify_revealed_outage(&mut self, checker: &CheckerConfig) {
+        if self.silenced.contains_key(&checker.id)
+            || !checker.notify_on.iter().any(|t| t.to == State::Down)
+        {
+            return;
+        }
+        let now = SystemTime::now();
+        let (last_error, down_since) = {
+            let states = self.states.lock().unwrap();
+            let checker_state = &states[&checker.id];
+            (checker_state.last_error.clone(), checker_state.changed_at)
+        };
+
+        if !checker.escalation.is_empty() {
+            let escalation = Escalation::new(checker, now, now);
+            self.escalations.insert(checker.id.clone(), escalation);
+        }
+        let notification = Notification {
+            checker_id: checker.id.clone(),
+            checker_url: checker.target(),
+            state: State::Down,
+            reason: last_error,
+            http_status: None,
+            response_time: None,
+            certificate_days_left: None,
+            outage_duration: down_since.and_then(|t| now.duration_since(t).ok()),
+            reminder: false,
+            after_maintenance: false,
+            affected: self.affected_by(&checker.id),
+            timestamp: now,
+        };
+        info!(
+            "Outage of {} is not caused by its dependencies anymore",
+            checker.id
+        );
+        for notifier_id in unique(&checker.notifiers) {
+            self.send_notification(notifier_id, &notification);
+            // Reminders start counting from this notification
+            self.reminded_at
+                .insert((checker.id.clone(), notifier_id.clone()), now);
+        }
+    }
+
+    // Performs the work, that depends on time rather than on incoming messages.
+    fn tick(&mut self) {
+        self.update_silences();
+        self.update_dependents();
+        self.check_ping_deadlines();
+        self.send_reminders();
+        self.escalate();
+    }
+
+    // Notifies the escalation steps, that are due.
+    fn escalate(&mut self) {
+        let now = SystemTime::now();
+        let mut due: Vec<(String, SystemTime, Vec<String>)> = vec![];
+        for (checker_id, escalation) in self.escalations.iter_mut() {
+            // Due steps wait until the maintenance ends
+            if self.silenced.contains_key(checker_id) {
+                continue;
+            }
+            let down_since = escalation.down_since;
+            let (due_steps, pending): (Vec<EscalationStep>, Vec<EscalationStep>) = escalation
+                .pending
+                .drain(..)
+                .partition(|step| down_since + step.after <= now);
+            escalation.pending = pending;
+
+            let notifier_ids: Vec<String> =
+                due_steps.into_iter().flat_map(|s| s.notifiers).collect();
+            if !notifier_ids.is_empty() {
+                escalation.notified.extend(notifier_ids.iter().cloned());
+                due.push((checker_id.clone(), down_since, notifier_ids));
+            }
+        }
+
+        for (checker_id, down_since, notifier_ids) in due {
+            let checker = match self.config.get_checker_by_id(&checker_id) {
+                Some(checker) => checker,
+                None => continue,
+            };
+            let last_error = self.states.lock().unwrap()[&checker_id].last_error.clone();
+            let notification = Notification {
+                checker_id: checker.id.clone(),
+                checker_url: checker.target(),
+                state: State::Down,
+                reason: last_error,
+                http_status: None,
+                response_time: None,
+                certificate_days_left: None,
+                outage_duration: now.duration_since(down_since).ok(),
+                reminder: false,
+                after_maintenance: false,
+                affected: self.affected_by(&checker.id),
+                timestamp: now,
+            };
+            info!("Escalating outage of {}", checker.id);
+            for notifier_id in unique(&notifier_ids) {
+                self.send_notification(notifier_id, &notification);
+                // Reminders start counting from the escalation
+                self.reminded_at
+                    .insert((checker_id.clone(), notifier_id.clone()), now);
+            }
+        }
+    }
+
+    // Tracks checkers entering and leaving maintenance windows and silences.
+    fn update_silences(&mut self) {
+        let now = SystemTime::now();
+        let silences = self.silences.lock().unwrap().active(now);
+        let mut ended: Vec<(CheckerConfig, Silenced)> = vec![];
+
+        {
+            let states = self.states.lock().unwrap();
+            for checker in self.config.checkers.iter() {
+                let summary =
+                    maintenance::silencing(checker, &self.config.maintenance, &silences, now);
+                match summary {
+                    Some(summary) => {
+                        let state = states[&checker.id].state.clone();
+                        let silenced =
+                            self.silenced.entry(checker.id.clone()).or_insert_with(|| {
+                                info!("Maintenance of {} has started", checker.id);
+                                Silenced {
+                                    state_before: state,
+                                    summary,
+                                }
+                            });
+                        // Overlapping windows may request the summary as well
+                        silenced.summary |= summary;
+                    }
+                    None => {
+                        if let Some(silenced) = self.silenced.remove(&checker.id) {
+                            info!("Maintenance of {} has ended", checker.id);
+                            ended.push((checker.clone(), silenced));
+                        }
+                    }
+                }
+            }
+        }
+
+        for (checker, silenced) in ended {
+            self.end_maintenance(&checker, silenced, now);
+        }
+    }
+
+    // Notifies about the state of the checker after maintenance, if it differs from the one
+    // before maintenance.
+    fn end_maintenance(&mut self, checker: &CheckerConfig, silenced: Silenced, now: SystemTime) {
+        let (state, last_error, changed_at) = {
+            let states = self.states.lock().unwrap();
+            let checker_state = &states[&checker.id];
+            (
+                checker_state.state.clone(),
+                checker_state.last_error.clone(),
+                checker_state.changed_at,
+            )
+        };
+        // Reminders about an outage, that has happened during maintenance, start from now
+        if state == State::Down {
+            for notifier_id in checker.notifiers.iter() {
+                self.reminded_at
+                    .insert((checker.id.clone(), notifier_id.clone()), now);
+            }
+        }
+
+        if !silenced.summary
+            || state == silenced.state_before
+            || !checker.notifies(&silenced.state_before, &state)
+        {
+            return;
+        }
+
+        if state == State::Down && !checker.escalation.is_empty() {
+            let escalation = Escalation::new(checker, now, now);
+            self.escalations.insert(checker.id.clone(), escalation);
+        }
+        let notification = Notification {
+            checker_id: checker.id.clone(),
+            checker_url: checker.target(),
+            state: state.clone(),
+            reason: if state == State::Up { None } else { last_error },
+            http_status: None,
+            response_time: None,
+            certificate_days_left: None,
+            outage_duration: match state {
+                State::Down => changed_at.and_then(|t| now.duration_since(t).ok()),
+                _ => None,
+            },
+            reminder: false,
+            after_maintenance: true,
+            affected: match state {
+                State::Down => self.affected_by(&checker.id),
+                _ => vec![],
+            },
+            timestamp: now,
+        };
+        for notifier_id in unique(&checker.notifiers) {
+            self.send_notification(notifier_id, &notification);
+        }
+    }
+
+    // Outages, that started before a restart or a reload, keep escalating from where they are.
+    fn restore_escalations(&mut self) {
+        let now = SystemTime::now();
+        let states = self.states.lock().unwrap();
+        self.escalations.clear();
+        for checker in self.config.checkers.iter() {
+            if checker.escalation.is_empty()
+                || !checker.notify_on.iter().any(|t| t.to == State::Down)
+            {
+                continue;
+            }
+            if let Some(checker_state) = states.get(&checker.id) {
+                if let (State::Down, Some(down_since), None) = (
+                    &checker_state.state,
+                    checker_state.changed_at,
+                    &checker_state.root_cause,
+                ) {
+                    let escalation = Escalation::new(checker, down_since, now);
+                    self.escalations.insert(checker.id.clone(), escalation);
+                }
+            }
+        }
+    }
+
+    // Times of the reminders sent before a restart are unknown, so reminders about the restored
+    // outages start counting from now. Otherwise all of them would be sent at once.
+    fn restore_reminders(&mut self) {
+        let now = SystemTime::now();
+        let states = self.states.lock().unwrap();
+        for checker in self.config.checkers.iter() {
+            if states.get(&checker.id).map(|s| &s.state) != Some(&State::Down) {
+                continue;
+            }
+            let mut notifier_ids = checker.notifiers.clone();
+            if let Some(escalation) = self.escalations.get(&checker.id) {
+                notifier_ids.extend(escalation.notified.iter().cloned());
+            }
+            for notifier_id in unique(&notifier_ids) {
+                self.reminded_at
+                    .insert((checker.id.clone(), notifier_id.clone()), now);
+            }
+        }
+    }
+
+    // Reports heartbeat checkers, that have not received a ping in time, as down.
+    fn check_ping_deadlines(&mut self) {
+        let now = SystemTime::now();
+        let missed: Vec<String> = self
+            .states
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, s)| {
+                s.ping_deadline
+                    .filter(|deadline| *deadline <= now)
+                    .is_some()
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for checker_id in missed {
+            let waited = match self.config.get_checker_by_id(&checker_id) {
+                Some(CheckerConfig {
+                    kind: CheckerKind::Heartbeat(ref heartbeat),
+                    ..
+                }) => heartbeat.period + heartbeat.grace,
+                _ => continue,
+            };
+            // Every missed deadline counts as a failed check, the next one is one period later
+            let msg = StateMessage {
+                checker_id,
+                state: State::Down,
+                reason: Some(Reason::MissedPing(waited)),
+                http_status: None,
+                response_time: None,
+                certificate_days_left: None,
+                timestamp: now,
+            };
+            self.handle_state_message(msg);
+        }
+    }
+
+    // Reminds that services are still down, every `remind_every` since the outage has started.
+    fn send_reminders(&mut self) {
+        let now = SystemTime::now();
+        let outages: Vec<(String, SystemTime, Option<Reason>)> = self
+            .states
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, s)| s.state == State::Down && s.root_cause.is_none())
+            .filter_map(|(id, s)| s.changed_at.map(|t| (id.clone(), t, s.last_error.clone())))
+            .collect();
+
+        for (checker_id, down_since, last_error) in outages {
+            let checker = match self.config.get_checker_by_id(&checker_id) {
+                Some(checker) => checker,
+                None => continue,
+            };
+            if self.silenced.contains_key(&checker_id) {
+                continue;
+            }
+            // Nobody wants to be reminded of an outage, that was not notified in the first place
+            if !checker.notify_on.iter().any(|t| t.to == State::Down) {
+                continue;
+            }
+
+            // Notifiers, that the outage has been escalated to, are reminded as well
+            let mut notifier_ids = checker.notifiers.clone();
+            if let Some(escalation) = self.escalations.get(&checker_id) {
+                notifier_ids.extend(escalation.notified.iter().cloned());
+            }
+
+            for notifier_id in unique(&notifier_ids) {
+                let remind_every = checker.remind_every.or_else(|| {
+                    self.config
+                        .get_notifier_by_id(notifier_id)
+                        .and_then(|n| n.remind_every)
+                });
+                let remind_every = match remind_every {
+                    Some(remind_every) => remind_every,
+                    None => continue,
+                };
+
+                // Reminders of the previous outages do not count
+                let key = (checker_id.clone(), notifier_id.clone());
+                let last_sent = self
+                    .reminded_at
+                    .get(&key)
+                    .cloned()
+                    .filter(|t| *t > down_since)
+                    .unwrap_or(down_since);
+                if now < last_sent + remind_every {
+                    continue;
+                }
+
+                let notification = Notification {
+                    checker_id: checker.id.clone(),
+                    checker_url: checker.target(),
+                    state: State::Down,
+                    reason: last_error.clone(),
+                    http_status: None,
+                    response_time: None,
+                    certificate_days_left: None,
+                    outage_duration: now.duration_since(down_since).ok(),
+                    reminder: true,
+                    after_maintenance: false,
+                    affected: self.affected_by(&checker.id),
+                    timestamp: now,
+                };
+                self.send_notification(notifier_id, &notification);
+                self.reminded_at.insert(key, now);
+            }
+        }
+    }
+
+    // Applies a new config. Known states of the checkers, that are still present, are preserved.
+    fn reload(&mut self, config: FileConfig) {
+        // Steps, that are due, are notified according to the previous config,
+        // while its checkers and notifiers are still there
+        self.escalate();
+        {
+            let mut states = self.states.lock().unwrap();
+            states.retain(|id, _| config.checkers.iter().any(|c| c.id == *id));
+            init_states(&config, &mut states);
+            self.persist_states(&states);
+        }
+        self.notifiers = build_notifiers(&config);
+        self.reminded_at
+            .retain(|(checker_id, _), _| config.checkers.iter().any(|c| c.id == *checker_id));
+        self.silenced
+            .retain(|checker_id, _| config.checkers.iter().any(|c| c.id == *checker_id));
+        self.config = config;
+        self.restore_escalations();
+        self.update_metrics();
+    }
+
+    // Exposes confirmed states of all the checkers and forgets the removed ones.
+    fn update_metrics(&self) {
+        let checker_ids: Vec<&str> = self.config.checkers.iter().map(|c| c.id.as_str()).collect();
+        let notifier_ids: Vec<&str> = self
+            .config
+            .notifiers
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect();
+        self.metrics.retain(&checker_ids, &notifier_ids);
+
+        for (id, checker_state) in self.states.lock().unwrap().iter() {
+            self.metrics.set_state(id, &checker_state.state);
+        }
+    }
+
+    fn persist_states(&self, states: &HashMap<String, CheckerState>) {
+        if let Some(ref path) = self.state_file {
+            if let Err(err) = persistence::save(path, states) {
+                eprintln!("ERROR: {}", err);
+            }
+        }
+    }
+}
+
+pub fn build_notification(checker: &CheckerConfig, msg: &StateMessage) -> Notification {
+    Notification {
+        checker_id: checker.id.clone(),
+        checker_url: checker.target(),
+        state: msg.state.clone(),
+        reason: msg.reason.clone(),
+        http_status: msg.http_status,
+        response_time: msg.response_time,
+        certificate_days_left: msg.certificate_days_left,
+        outage_duration: None,
+        reminder: false,
+        after_maintenance: false,
+        affected: vec![],
+        timestamp: msg.timestamp,
+    }
+}
+
+// Dependency, that is down and explains why the checker is down.
+fn find_root_cause(
+    checker: &CheckerConfig,
+    state: &State,
+    states: &HashMap<String, CheckerState>,
+) -> Option<String> {
+    if *state != State::Down {
+        return None;
+    }
+    checker.depends_on.iter().find_map(|dependency_id| {
+        states
+            .get(dependency_id)
+            .filter(|s| s.state == State::Down)
+            // Dependencies may be caused by their own dependencies
+            .map(|s| {
+                s.root_cause
+                    .clone()
+                    .unwrap_or_else(|| dependency_id.clone())
+            })
+    })
+}
+
+// Notifier ids without duplicates, in the original order.
+fn unique(ids: &[String]) -> Vec<&String> {
+    let mut result: Vec<&String> = vec![];
+    for id in ids.iter() {
+        if !result.contains(&id) {
+            result.push(id);
+        }
+    }
+    result
+}
+
+// Time by which a heartbeat checker expects the next ping after `since`.
+fn ping_deadline(checker: &CheckerConfig, since: SystemTime) -> Option<SystemTime> {
+    match checker.kind {
+        CheckerKind::Heartbeat(ref heartbeat) => Some(since + heartbeat.period + heartbeat.grace),
+        _ => None,
+    }
+}
+
+// Every checker, that has no known state yet, is assumed to be up.
+// Heartbeat checkers, that have not received a ping yet, wait for a full period.
+fn init_states(config: &FileConfig, states: &mut HashMap<String, CheckerState>) {
+    let now = SystemTime::now();
+    for checker in config.checkers.iter() {
+        let checker_state = states
+            .entry(checker.id.clone())
+            .or_insert_with(|| CheckerState {
+                state: State::Up,
+                mismatches: 0,
+                changed_at: None,
+                url: String::new(),
+                last_checked_at: None,
+                last_error: None,
+                ping_deadline: None,
+                root_cause: None,
+            });
+        // URL might have been changed by a reload
+        checker_state.url = checker.target();
+        // So might the period of a heartbeat checker
+        let since = checker_state.last_checked_at.unwrap_or(now);
+        checker_state.ping_deadline = ping_deadline(checker, since);
+    }
+}
+
+pub fn build_notifiers(config: &FileConfig) -> HashMap<String, Box<dyn NotifierTrait>> {
+    let mut notifiers: HashMap<String, Box<dyn NotifierTrait>> = HashMap::new();
+    for notifier_config in config.notifiers.iter() {
+        let notifier: Box<dyn NotifierTrait> = match &notifier_config.config {
+            NotifierConfig::Telegram(telegram_config) => {
+                Box::new(TelegramNotifier::from_config(telegram_config))
+            }
+            NotifierConfig::Command(command_config) => {
+                Box::new(CommandNotifier::from_config(command_config))
+            }
+            NotifierConfig::Hipchat(hipchat_config) => {
+                Box::new(HipchatNotifier::from_config(hipchat_config))
+            }
+            NotifierConfig::Slack(slack_config) => {
+                Box::new(SlackNotifier::from_config(slack_config))
+            }
+        };
+        notifiers.insert(notifier_config.id.clone(), notifier);
+    }
+
+    notifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::parse_config;
+    use crate::config::MaintenanceScope;
+    use crate::maintenance::SharedSilences;
+
+    // Records notifications as "<checker> is <state>", followed by the reason and affected checkers.
+    // The text is prefixed with the notifier id, if it's given.
+    struct RecordingNotifier(Arc<Mutex<Vec<String>>>, Option<String>);
+
+    impl NotifierTrait for RecordingNotifier {
+        fn notify(&self, notification: &Notification) -> Result<(), ()> {
+            let mut text = format!(
+                "{} is {}",
+                notification.checker_id,
+                notification.state_text()
+            );
+            if let Some(ref notifier_id) = self.1 {
+                text = format!("{}: {}", notifier_id, text);
+            }
+            if let Some(ref reason) = notification.reason {
+                text.push_str(&format!(" ({})", reason));
+            }
+            if !notification.affected.is_empty() {
+                text.push_str(&format!(
+                    ", also down: {}",
+                    notification.affected.join(", ")
+                ));
+            }
+            self.0.lock().unwrap().push(text);
+            Ok(())
+        }
+    }
+
+    fn build_reactor(yaml: &str) -> (Reactor, Arc<Mutex<Vec<String>>>) {
+        let config = parse_config(yaml).unwrap();
+        let mut reactor = Reactor::new(
+            config,
+            None,
+            Metrics::default(),
+            SharedStates::default(),
+            SharedSilences::default(),
+        );
+        let sent = Arc::new(Mutex::new(vec![]));
+        record_notifications(&mut reactor, &sent);
+        (reactor, sent)
+    }
+
+    // Replaces the notifiers built from the config
+    fn record_notifications(reactor: &mut Reactor, sent: &Arc<Mutex<Vec<String>>>) {
+        let notifier_ids: Vec<String> = reactor.notifiers.keys().cloned().collect();
+        for id in notifier_ids {
+            let notifier = RecordingNotifier(Arc::clone(sent), None);
+            reactor.notifiers.insert(id, Box::new(notifier));
+        }
+    }
+
+    fn report(reactor: &mut Reactor, checker_id: &str, state: State) {
+        let msg = StateMessage {
+            checker_id: checker_id.to_owned(),
+            state,
+            reason: None,
+            http_status: None,
+            response_time: Some(Duration::from_millis(10)),
+            certificate_days_left: None,
+            timestamp: SystemTime::now(),
+        };
+        reactor.handle_state_message(msg);
+        reactor.tick();
+    }
+
+    fn take(sent: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+        sent.lock().unwrap().drain(..).collect()
+    }
+
+    #[test]
+    fn test_dependency_outage() {
+        let yaml = r#"
+            checkers:
+              lb:
+                url: https://lb.example.com/
+                notifiers: [ops]
+              api:
+                url: https://api.example.com/
+                depends_on: [lb]
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+        "#;
+        let (mut reactor, sent) = build_reactor(yaml);
+
+        report(&mut reactor, "lb", State::Down);
+        assert_eq!(take(&sent), vec!["lb is down"]);
+        report(&mut reactor, "api", State::Down);
+        assert_eq!(take(&sent), Vec::<String>::new());
+
+        // api stays down, when its dependency recovers
+        report(&mut reactor, "lb", State::Up);
+        assert_eq!(take(&sent), vec!["lb is up", "api is down"]);
+        report(&mut reactor, "api", State::Up);
+        assert_eq!(take(&sent), vec!["api is up"]);
+
+        // Recovery of a suppressed outage is suppressed as well
+        report(&mut reactor, "lb", State::Down);
+        report(&mut reactor, "api", State::Down);
+        report(&mut reactor, "api", State::Up);
+        assert_eq!(take(&sent), vec!["lb is down"]);
+    }
+
+    #[test]
+    fn test_affected_dependents() {
+        let yaml = r#"
+            checkers:
+              lb:
+                url: https://lb.example.com/
+                notifiers: [ops]
+                remind_every: 1h
+              api:
+                url: https://api.example.com/
+                depends_on: [lb]
+                notifiers: [ops]
+              web:
+                url: https://www.example.com/
+                depends_on: [api]
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+        "#;
+        let (mut reactor, sent) = build_reactor(yaml);
+
+        report(&mut reactor, "lb", State::Down);
+        report(&mut reactor, "api", State::Down);
+        report(&mut reactor, "web", State::Down);
+        assert_eq!(take(&sent), vec!["lb is down"]);
+
+        // Suppressed outages are mentioned by the reminder about the root cause
+        {
+            let mut states = reactor.states.lock().unwrap();
+            let lb = states.get_mut("lb").unwrap();
+            lb.changed_at = lb.changed_at.map(|t| t - Duration::from_secs(60 * 60));
+        }
+        reactor.tick();
+        assert_eq!(take(&sent), vec!["lb is still down, also down: api, web"]);
+
+        report(&mut reactor, "lb", State::Up);
+        assert_eq!(take(&sent), vec!["lb is up", "api is down, also down: web"]);
+    }
+
+    #[test]
+    fn test_reload_removes_escalation_notifier() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                notifiers: [ops]
+                escalation:
+                  - after: 0s
+                    notifiers: [pager]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+              pager:
+                type: command
+                command: ["true"]
+        "#;
+        let (mut reactor, sent) = build_reactor(yaml);
+        let msg = StateMessage {
+            checker_id: "api".to_owned(),
+            state: State::Down,
+            reason: None,
+            http_status: None,
+            response_time: Some(Duration::from_millis(10)),
+            certificate_days_left: None,
+            timestamp: SystemTime::now(),
+        };
+        reactor.handle_state_message(msg);
+        assert_eq!(take(&sent), vec!["api is down"]);
+
+        // The due step is escalated before `pager` is removed
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+        "#;
+        reactor.reload(parse_config(yaml).unwrap());
+        assert!(!reactor.notifiers.contains_key("pager"));
+        assert_eq!(take(&sent), vec!["api is down"]);
+
+        record_notifications(&mut reactor, &sent);
+        report(&mut reactor, "api", State::Up);
+        assert_eq!(take(&sent), vec!["api is up"]);
+    }
+
+    #[test]
+    fn test_reload() {
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                notifiers: [ops]
+              web:
+                url: https://www.example.com/
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+        "#;
+        let (mut reactor, sent) = build_reactor(yaml);
+        report(&mut reactor, "api", State::Down);
+        report(&mut reactor, "web", State::Down);
+        assert_eq!(take(&sent), vec!["api is down", "web is down"]);
+
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/v2/
+                notifiers: [ops]
+              db:
+                type: tcp
+                address: db.example.com:5432
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+        "#;
+        reactor.reload(parse_config(yaml).unwrap());
+        record_notifications(&mut reactor, &sent);
+        {
+            let states = reactor.states.lock().unwrap();
+            let mut ids: Vec<&String> = states.keys().collect();
+            ids.sort();
+            assert_eq!(ids, vec!["api", "db"]);
+            // Known state is preserved, while the URL is updated
+            assert_eq!(states["api"].state, State::Down);
+            assert_eq!(states["api"].url, "https://api.example.com/v2/");
+            assert_eq!(states["db"].state, State::Up);
+        }
+
+        // Messages of the removed checkers are ignored
+        report(&mut reactor, "web", State::Up);
+        report(&mut reactor, "api", State::Up);
+        assert_eq!(take(&sent), vec!["api is up"]);
+    }
+
+    // Config with a single `api` checker, `attrs` are appended to its attributes
+    fn single_checker_yaml(attrs: &str) -> String {
+        format!(
+            r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                notifiers: [ops]
+                {}
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+            "#,
+            attrs
+        )
+    }
+
+    #[test]
+    fn test_thresholds() {
+        use State::*;
+
+        // (fail_threshold, success_threshold, reported states, expected notifications)
+        let cases: Vec<(u32, u32, Vec<State>, Vec<&str>)> = vec![
+            (1, 1, vec![Down, Up], vec!["api is down", "api is up"]),
+            (2, 1, vec![Down], vec![]),
+            (2, 1, vec![Down, Down, Up], vec!["api is down", "api is up"]),
+            // A matching result resets the counter
+            (2, 1, vec![Down, Up, Down], vec![]),
+            (
+                3,
+                1,
+                vec![Down, Down, Up, Down, Down, Down],
+                vec!["api is down"],
+            ),
+            (1, 2, vec![Down, Up], vec!["api is down"]),
+            (1, 2, vec![Down, Up, Down, Up], vec!["api is down"]),
+            (1, 2, vec![Down, Up, Up], vec!["api is down", "api is up"]),
+            // Degraded state is confirmed the same way
+            (2, 1, vec![Degraded, Degraded], vec!["api is degraded"]),
+            (
+                1,
+                1,
+                vec![Degraded, Down, Degraded, Up],
+                vec![
+                    "api is degraded",
+                    "api is down",
+                    "api is degraded",
+                    "api is up",
+                ],
+            ),
+            // Mismatches are counted regardless of the reported state
+            (2, 1, vec![Degraded, Down], vec!["api is down"]),
+            // Recovery from down to degraded is confirmed by success_threshold
+            (1, 2, vec![Down, Degraded], vec!["api is down"]),
+            (
+                1,
+                2,
+                vec![Down, Degraded, Degraded],
+                vec!["api is down", "api is degraded"],
+            ),
+            (
+                1,
+                2,
+                vec![Degraded, Down, Up, Up],
+                vec!["api is degraded", "api is down", "api is up"],
+            ),
+        ];
+
+        for (fail_threshold, success_threshold, states, expected) in cases {
+            let attrs = format!(
+                "fail_threshold: {}\n                success_threshold: {}",
+                fail_threshold, success_threshold
+            );
+            let (mut reactor, sent) = build_reactor(&single_checker_yaml(&attrs));
+            for state in states.clone() {
+                report(&mut reactor, "api", state);
+            }
+            assert_eq!(
+                take(&sent),
+                expected,
+                "fail_threshold: {}, success_threshold: {}, states: {:?}",
+                fail_threshold,
+                success_threshold,
+                states
+            );
+        }
+    }
+
+    #[test]
+    fn test_notify_on() {
+        use State::*;
+
+        let states = vec![Degraded, Down, Degraded, Up, Down, Up];
+        // (notify_on, expected notifications)
+        let cases = vec![
+            (
+                "[up, degraded, down]",
+                vec![
+                    "api is degraded",
+                    "api is down",
+                    "api is degraded",
+                    "api is up",
+                    "api is down",
+                    "api is up",
+                ],
+            ),
+            ("down", vec!["api is down", "api is down"]),
+            (
+                "[down, up]",
+                vec!["api is down", "api is up", "api is down", "api is up"],
+            ),
+            ("\"down -> up\"", vec!["api is up"]),
+            (
+                "[down, \"down -> degraded\"]",
+                vec!["api is down", "api is degraded", "api is down"],
+            ),
+            ("\"up -> degraded\"", vec!["api is degraded"]),
+        ];
+
+        for (notify_on, expected) in cases {
+            let attrs = format!("notify_on: {}", notify_on);
+            let (mut reactor, sent) = build_reactor(&single_checker_yaml(&attrs));
+            for state in states.clone() {
+                report(&mut reactor, "api", state);
+            }
+            assert_eq!(take(&sent), expected, "notify_on: {}", notify_on);
+        }
+    }
+
+    #[test]
+    fn test_reminder_after_restore() {
+        let path = std::env::temp_dir().join("hellcheck_test_reminder_after_restore.json");
+        let mut states = HashMap::new();
+        let checker_state = CheckerState {
+            state: State::Down,
+            mismatches: 0,
+            changed_at: Some(SystemTime::now() - Duration::from_secs(2 * 60 * 60)),
+            url: String::new(),
+            last_checked_at: None,
+            last_error: Some(Reason::UnexpectedStatus(500)),
+            ping_deadline: None,
+            root_cause: None,
+        };
+        states.insert("api".to_owned(), checker_state);
+        persistence::save(&path, &states).unwrap();
+
+        let config = parse_config(&single_checker_yaml("remind_every: 1h")).unwrap();
+        let mut reactor = Reactor::new(
+            config,
+            Some(path.clone()),
+            Metrics::default(),
+            SharedStates::default(),
+            SharedSilences::default(),
+        );
+        let sent = Arc::new(Mutex::new(vec![]));
+        record_notifications(&mut reactor, &sent);
+        std::fs::remove_file(&path).unwrap();
+
+        // Reminders are not sent all at once right after a restart
+        reactor.tick();
+        assert_eq!(take(&sent), Vec::<String>::new());
+
+        for reminded_at in reactor.reminded_at.values_mut() {
+            *reminded_at -= Duration::from_secs(60 * 60);
+        }
+        reactor.tick();
+        assert_eq!(
+            take(&sent),
+            vec!["api is still down (Unexpected HTTP status 500 Internal Server Error)"]
+        );
+    }
+
+    #[test]
+    fn test_build_notification() {
+        let config = parse_config(
+            r#"
+            checkers:
+              db:
+                type: tcp
+                address: db.example.com:5432
+        "#,
+        )
+        .unwrap();
+        let msg = StateMessage {
+            checker_id: "db".to_owned(),
+            state: State::Up,
+            reason: None,
+            http_status: None,
+            response_time: Some(Duration::from_millis(30)),
+            certificate_days_left: None,
+            timestamp: SystemTime::now(),
+        };
+        let notification = build_notification(&config.checkers[0], &msg);
+        assert_eq!(notification.checker_url, "db.example.com:5432");
+        assert_eq!(notification.http_status, None);
+        assert_eq!(notification.response_time, Some(Duration::from_millis(30)));
+    }
+
+    // Moves the timestamps of the outages, reminders and escalations back,
+    // as if the time has passed
+    fn rewind(reactor: &mut Reactor, minutes: u64) {
+        let duration = Duration::from_secs(minutes * 60);
+        for checker_state in reactor.states.lock().unwrap().values_mut() {
+            checker_state.changed_at = checker_state.changed_at.map(|t| t - duration);
+        }
+        for reminded_at in reactor.reminded_at.values_mut() {
+            *reminded_at -= duration;
+        }
+        for escalation in reactor.escalations.values_mut() {
+            escalation.down_since -= duration;
+        }
+        reactor.tick();
+    }
+
+    #[derive(Debug)]
+    enum Step {
+        Report(State),
+        Wait(u64),
+        Silence,
+        // Restarts with the states persisted, the recorded notifiers are kept
+        Restart,
+    }
+
+    // Config of `api` checker and `ops` notifier with the given attributes
+    fn checker_with_notifier_yaml(checker_attrs: &str, notifier_attrs: &str) -> String {
+        format!(
+            r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                notifiers: [ops]
+                {}
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+                {}
+            "#,
+            checker_attrs, notifier_attrs
+        )
+    }
+
+    fn run_steps(reactor: &mut Reactor, steps: &[Step]) {
+        for step in steps {
+            match *step {
+                Step::Report(ref state) => report(reactor, "api", state.clone()),
+                Step::Wait(minutes) => rewind(reactor, minutes),
+                Step::Silence => {
+                    let scope = MaintenanceScope::default();
+                    let duration = Duration::from_secs(24 * 60 * 60);
+                    reactor
+                        .silences
+                        .lock()
+                        .unwrap()
+                        .add(scope, duration, None, false);
+                    reactor.tick();
+                }
+                Step::Restart => {
+                    let path = std::env::temp_dir().join("hellcheck_test_restart.json");
+                    persistence::save(&path, &reactor.states.lock().unwrap()).unwrap();
+                    let notifiers = std::mem::take(&mut reactor.notifiers);
+                    *reactor = Reactor::new(
+                        reactor.config.clone(),
+                        Some(path.clone()),
+                        Metrics::default(),
+                        SharedStates::default(),
+                        SharedSilences::default(),
+                    );
+                    reactor.notifiers = notifiers;
+                    reactor.state_file = None;
+                    std::fs::remove_file(&path).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reminders() {
+        use State::*;
+        use Step::*;
+
+        // (checker attributes, notifier attributes, steps, expected notifications)
+        let cases = vec![
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Wait(59)],
+                vec!["api is down"],
+            ),
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Wait(60)],
+                vec!["api is down", "api is still down"],
+            ),
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Wait(60), Wait(30)],
+                vec!["api is down", "api is still down"],
+            ),
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Wait(60), Wait(60)],
+                vec!["api is down", "api is still down", "api is still down"],
+            ),
+            // Degraded services are not reminded about
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Degraded), Wait(120)],
+                vec!["api is degraded"],
+            ),
+            // No reminders after recovery
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Report(Up), Wait(120)],
+                vec!["api is down", "api is up"],
+            ),
+            // Reminders of the previous outage do not count
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Wait(60), Report(Up), Report(Down), Wait(30)],
+                vec![
+                    "api is down",
+                    "api is still down",
+                    "api is up",
+                    "api is down",
+                ],
+            ),
+            // No reminders while silenced
+            (
+                "remind_every: 1h",
+                "",
+                vec![Report(Down), Silence, Wait(120)],
+                vec!["api is down"],
+            ),
+            // Outage, that was not notified, is not reminded about
+            (
+                "remind_every: 1h\n                notify_on: up",
+                "",
+                vec![Report(Down), Wait(120)],
+                vec![],
+            ),
+            // Notifier's remind_every is used, unless the checker has its own
+            (
+                "",
+                "remind_every: 30m",
+                vec![Report(Down), Wait(30)],
+                vec!["api is down", "api is still down"],
+            ),
+            (
+                "remind_every: 1h",
+                "remind_every: 30m",
+                vec![Report(Down), Wait(30)],
+                vec!["api is down"],
+            ),
+            (
+                "",
+                "",
+                vec![Report(Down), Wait(24 * 60)],
+                vec!["api is down"],
+            ),
+        ];
+
+        for (checker_attrs, notifier_attrs, steps, expected) in cases {
+            let yaml = checker_with_notifier_yaml(checker_attrs, notifier_attrs);
+            let (mut reactor, sent) = build_reactor(&yaml);
+            run_steps(&mut reactor, &steps);
+            assert_eq!(
+                take(&sent),
+                expected,
+                "checker: {:?}, notifier: {:?}, steps: {:?}",
+                checker_attrs,
+                notifier_attrs,
+                steps
+            );
+        }
+    }
+
+    // Replaces the notifiers built from the config, notifications are prefixed with notifier ids
+    fn record_notifications_by_notifier(reactor: &mut Reactor, sent: &Arc<Mutex<Vec<String>>>) {
+        let notifier_ids: Vec<String> = reactor.notifiers.keys().cloned().collect();
+        for id in notifier_ids {
+            let notifier = RecordingNotifier(Arc::clone(sent), Some(id.clone()));
+            reactor.notifiers.insert(id, Box::new(notifier));
+        }
+    }
+
+    #[test]
+    fn test_escalation() {
+        use State::*;
+        use Step::*;
+
+        let yaml = r#"
+            checkers:
+              api:
+                url: https://api.example.com/
+                notifiers: [ops]
+                escalation:
+                  - after: 10m
+                    notifiers: [lead]
+                  - after: 30m
+                    notifiers: [pager]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+              lead:
+                type: command
+                command: ["true"]
+              pager:
+                type: command
+                command: ["true"]
+        "#;
+        let cases: Vec<(Vec<Step>, Vec<&str>)> = vec![
+            (vec![Report(Down), Wait(9)], vec!["ops: api is down"]),
+            (
+                vec![Report(Down), Wait(10)],
+                vec!["ops: api is down", "lead: api is down"],
+            ),
+            // Every step is notified once
+            (
+                vec![Report(Down), Wait(10), Wait(10), Wait(10)],
+                vec![
+                    "ops: api is down",
+                    "lead: api is down",
+                    "pager: api is down",
+                ],
+            ),
+            (
+                vec![Report(Down), Wait(60)],
+                vec![
+                    "ops: api is down",
+                    "lead: api is down",
+                    "pager: api is down",
+                ],
+            ),
+            // Notified steps are told about the recovery, pending ones are cancelled
+            (
+                vec![Report(Down), Wait(10), Report(Up), Wait(60)],
+                vec![
+                    "ops: api is down",
+                    "lead: api is down",
+                    "ops: api is up",
+                    "lead: api is up",
+                ],
+            ),
+            (
+                vec![Report(Down), Report(Up), Wait(60)],
+                vec!["ops: api is down", "ops: api is up"],
+            ),
+            // Due steps wait until the silence ends
+            (
+                vec![Report(Down), Silence, Wait(60)],
+                vec!["ops: api is down"],
+            ),
+            // Escalation continues after a restart, without repeating the notified steps
+            (
+                vec![Report(Down), Restart, Wait(10)],
+                vec!["ops: api is down", "lead: api is down"],
+            ),
+            (
+                vec![Report(Down), Wait(10), Restart, Wait(10), Wait(10)],
+                vec![
+                    "ops: api is down",
+                    "lead: api is down",
+                    "pager: api is down",
+                ],
+            ),
+            (
+                vec![Report(Down), Wait(10), Restart, Report(Up)],
+                vec![
+                    "ops: api is down",
+                    "lead: api is down",
+                    "ops: api is up",
+                    "lead: api is up",
+                ],
+            ),
+        ];
+
+        for (steps, expected) in cases {
+            let (mut reactor, sent) = build_reactor(yaml);
+            record_notifications_by_notifier(&mut reactor, &sent);
+            run_steps(&mut reactor, &steps);
+            assert_eq!(take(&sent), expected, "steps: {:?}", steps);
+        }
+    }
+
+    #[test]
+    fn test_missed_ping() {
+        let yaml = r#"
+            checkers:
+              backup:
+                type: heartbeat
+                period: 1h
+                grace: 10m
+                notifiers: [ops]
+            notifiers:
+              ops:
+                type: command
+                command: ["true"]
+        "#;
+        let (mut reactor, sent) = build_reactor(yaml);
+        let deadline = |reactor: &Reactor| reactor.states.lock().unwrap()["backup"].ping_deadline;
+
+        // The first ping is awaited for a full period
+        let first_deadline = deadline(&reactor).unwrap();
+        assert!(first_deadline > SystemTime::now() + Duration::from_secs(60 * 60));
+        reactor.tick();
+        assert_eq!(take(&sent), Vec::<String>::new());
+
+        let missed = SystemTime::now() - Duration::from_secs(1);
+        reactor
+            .states
+            .lock()
+            .unwrap()
+            .get_mut("backup")
+            .unwrap()
+            .ping_deadline = Some(missed);
+        reactor.tick();
+        assert_eq!(
+            take(&sent),
+            vec!["backup is down (No ping received within 1h 10m)"]
+        );
+        // The next missed ping is awaited one period later
+        assert!(deadline(&reactor).unwrap() > missed);
+
+        report(&mut reactor, "backup", State::Up);
+        assert_eq!(take(&sent), vec!["backup is up"]);
+        assert!(deadline(&reactor).unwrap() > SystemTime::now() + Duration::from_secs(60 * 60));
+    }
+}
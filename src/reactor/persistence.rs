@@ -0,0 +1,174 @@
+// Persists known states of the checkers into a JSON file, so they survive restarts.
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{CheckerState, Reason, State};
+
+#[derive(Serialize, Deserialize)]
+struct StateFile {
+    checkers: HashMap<String, PersistedState>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedState {
+    state: State,
+    mismatches: u32,
+    // Unix timestamp of the last state change
+    changed_at: Option<u64>,
+    // Outages caused by dependencies stay unnotified after a restart
+    #[serde(default)]
+    root_cause: Option<String>,
+    // Reminders about the outage repeat the reason
+    #[serde(default)]
+    last_error: Option<Reason>,
+}
+
+// Returns an empty map if the file does not exist yet.
+pub fn load(path: &Path) -> Result<HashMap<String, CheckerState>, String> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read state file {}: {}", path.display(), err))?;
+    let state_file: StateFile = serde_json::from_str(&content)
+        .map_err(|err| format!("Failed to parse state file {}: {}", path.display(), err))?;
+
+    let states = state_file
+        .checkers
+        .into_iter()
+        .map(|(id, persisted)| {
+            let checker_state = CheckerState {
+                state: persisted.state,
+                mismatches: persisted.mismatches,
+                changed_at: persisted
+                    .changed_at
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                // The rest is filled by the reactor
+                url: String::new(),
+                last_checked_at: None,
+                last_error: persisted.last_error,
+                ping_deadline: None,
+                root_cause: persisted.root_cause,
+            };
+            (id, checker_state)
+        })
+        .collect();
+    Ok(states)
+}
+
+pub fn save(path: &Path, states: &HashMap<String, CheckerState>) -> Result<(), String> {
+    let checkers = states
+        .iter()
+        .map(|(id, checker_state)| {
+            let persisted = PersistedState {
+                state: checker_state.state.clone(),
+                mismatches: checker_state.mismatches,
+                changed_at: checker_state.changed_at.map(to_unix_timestamp),
+                root_cause: checker_state.root_cause.clone(),
+                last_error: checker_state.last_error.clone(),
+            };
+            (id.clone(), persisted)
+        })
+        .collect();
+    let state_file = StateFile { checkers };
+
+    // unwrap is safe here, because the structure is always serializable
+    let content = serde_json::to_string_pretty(&state_file).unwrap();
+
+    // Write to a temporary file first, so the state file is never left half-written
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)
+        .and_then(|()| fs::rename(&tmp_path, path))
+        .map_err(|err| format!("Failed to write state file {}: {}", path.display(), err))
+}
+
+fn to_unix_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hellcheck_test_{}.json", name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn checker_state(state: State, root_cause: Option<&str>) -> CheckerState {
+        CheckerState {
+            state,
+            mismatches: 1,
+            changed_at: Some(UNIX_EPOCH + Duration::from_secs(1_549_335_600)),
+            url: "https://api.example.com/".to_owned(),
+            last_checked_at: Some(SystemTime::now()),
+            last_error: None,
+            ping_deadline: None,
+            root_cause: root_cause.map(|id| id.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = temp_path("save_and_load");
+        let mut states = HashMap::new();
+        let mut lb = checker_state(State::Down, None);
+        lb.last_error = Some(Reason::Timeout(Duration::from_secs(5)));
+        states.insert("lb".to_owned(), lb);
+        states.insert("api".to_owned(), checker_state(State::Down, Some("lb")));
+        save(&path, &states).unwrap();
+
+        let loaded = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        let api = &loaded["api"];
+        assert_eq!(api.state, State::Down);
+        assert_eq!(api.mismatches, 1);
+        assert_eq!(api.changed_at, states["api"].changed_at);
+        assert_eq!(api.root_cause, Some("lb".to_owned()));
+        assert_eq!(loaded["lb"].root_cause, None);
+        assert_eq!(
+            loaded["lb"].last_error,
+            Some(Reason::Timeout(Duration::from_secs(5)))
+        );
+        // Not persisted, filled by the reactor
+        assert_eq!(api.url, "");
+        assert_eq!(api.last_checked_at, None);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let path = temp_path("missing");
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_corrupt_file() {
+        let path = temp_path("corrupt");
+        fs::write(&path, "{ not json").unwrap();
+        let err = load(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(err.starts_with("Failed to parse state file"));
+    }
+
+    #[test]
+    fn test_load_file_without_root_causes() {
+        let path = temp_path("without_root_causes");
+        let content = r#"{"checkers": {"api": {"state": "down", "mismatches": 0, "changed_at": 1549335600}}}"#;
+        fs::write(&path, content).unwrap();
+        let loaded = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded["api"].state, State::Down);
+        assert_eq!(loaded["api"].root_cause, None);
+    }
+}
@@ -0,0 +1,83 @@
+// Built-in HTTP server, that serves the status page, the status API,
+// receives pings of heartbeat checkers and manages silences.
+
+use hyper::rt::Future;
+use hyper::service::service_fn_ok;
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+use std::sync::mpsc;
+
+use crate::config::ServerConfig;
+use crate::maintenance::SharedSilences;
+use crate::reactor::{Message, SharedStates};
+
+mod ping;
+mod silences;
+mod status;
+
+// Starts the server in a separate thread.
+pub fn spawn(
+    config: &ServerConfig,
+    states: SharedStates,
+    silences: SharedSilences,
+    sender: mpsc::Sender<Message>,
+) {
+    let builder = match Server::try_bind(&config.listen) {
+        Ok(builder) => builder,
+        Err(err) => {
+            eprintln!(
+                "ERROR: Failed to start server on {}: {}",
+                config.listen, err
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let admin_token = config.admin_token.clone();
+    let server = builder
+        .serve(move || {
+            let states = states.clone();
+            let silences = silences.clone();
+            let admin_token = admin_token.clone();
+            let sender = sender.clone();
+            service_fn_ok(move |req: Request<Body>| {
+                route(&req, &states, &silences, &admin_token, &sender)
+            })
+        })
+        .map_err(|err| eprintln!("ERROR: Server failed: {}", err));
+
+    info!("Serving status page on http://{}/", config.listen);
+    std::thread::spawn(move || hyper::rt::run(server));
+}
+
+fn route(
+    req: &Request<Body>,
+    states: &SharedStates,
+    silences: &SharedSilences,
+    admin_token: &Option<String>,
+    sender: &mpsc::Sender<Message>,
+) -> Response<Body> {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/") => status::html(states),
+        (&Method::GET, "/api/status") => status::json(states),
+        (_, path) if silences::is_api_path(path) => silences::handle(req, admin_token, silences),
+        (&Method::GET, path) | (&Method::POST, path) if ping::is_ping_path(path) => {
+            ping::handle(path, states, sender)
+        }
+        _ => text_response(StatusCode::NOT_FOUND, "Not Found"),
+    }
+}
+
+fn text_response(status: StatusCode, text: &str) -> Response<Body> {
+    respond(status, "text/plain; charset=utf-8", text.to_owned())
+}
+
+fn respond(status: StatusCode, content_type: &'static str, body: String) -> Response<Body> {
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = status;
+    resp.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(content_type),
+    );
+    resp
+}
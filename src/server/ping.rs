@@ -0,0 +1,123 @@
+use hyper::{Body, Response, StatusCode};
+
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+use super::text_response;
+use crate::reactor::{Message, Reason, SharedStates, State, StateMessage};
+
+const PATH_PREFIX: &str = "/ping/";
+
+// Receives a ping of a heartbeat checker: `/ping/<id>` reports a success,
+// `/ping/<id>/fail` reports a failure.
+pub fn handle(path: &str, states: &SharedStates, sender: &mpsc::Sender<Message>) -> Response<Body> {
+    let path = path.trim_start_matches(PATH_PREFIX);
+    let (checker_id, state, reason) = match path.strip_suffix("/fail") {
+        Some(checker_id) => {
+            let reason = Reason::Other("Failure was reported by ping".to_owned());
+            (checker_id, State::Down, Some(reason))
+        }
+        None => (path, State::Up, None),
+    };
+
+    // Only heartbeat checkers have a deadline for the next ping
+    let is_heartbeat = states
+        .lock()
+        .unwrap()
+        .get(checker_id)
+        .and_then(|s| s.ping_deadline)
+        .is_some();
+    if !is_heartbeat {
+        let text = format!("Unknown heartbeat checker `{}`", checker_id);
+        return text_response(StatusCode::NOT_FOUND, &text);
+    }
+
+    let msg = StateMessage {
+        checker_id: checker_id.to_owned(),
+        state,
+        reason,
+        http_status: None,
+        response_time: None,
+        certificate_days_left: None,
+        timestamp: SystemTime::now(),
+    };
+    match sender.send(Message::State(msg)) {
+        Ok(_) => text_response(StatusCode::OK, "OK"),
+        Err(_) => text_response(StatusCode::SERVICE_UNAVAILABLE, "Service Unavailable"),
+    }
+}
+
+pub fn is_ping_path(path: &str) -> bool {
+    path.starts_with(PATH_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactor::CheckerState;
+    use hyper::StatusCode;
+
+    fn build_states() -> SharedStates {
+        let checker_state = |ping_deadline| CheckerState {
+            state: State::Up,
+            mismatches: 0,
+            changed_at: None,
+            url: String::new(),
+            last_checked_at: None,
+            last_error: None,
+            ping_deadline,
+            root_cause: None,
+        };
+        let states = SharedStates::default();
+        {
+            let mut map = states.lock().unwrap();
+            map.insert("backup".to_owned(), checker_state(Some(SystemTime::now())));
+            map.insert("web".to_owned(), checker_state(None));
+        }
+        states
+    }
+
+    #[test]
+    fn test_ping() {
+        let states = build_states();
+        let (sender, receiver) = mpsc::channel();
+
+        let response = handle("/ping/backup", &states, &sender);
+        assert_eq!(response.status(), StatusCode::OK);
+        match receiver.try_recv() {
+            Ok(Message::State(msg)) => {
+                assert_eq!(msg.checker_id, "backup");
+                assert_eq!(msg.state, State::Up);
+                assert_eq!(msg.reason, None);
+            }
+            _ => panic!("Expected state message"),
+        }
+
+        let response = handle("/ping/backup/fail", &states, &sender);
+        assert_eq!(response.status(), StatusCode::OK);
+        match receiver.try_recv() {
+            Ok(Message::State(msg)) => {
+                assert_eq!(msg.checker_id, "backup");
+                assert_eq!(msg.state, State::Down);
+                assert_eq!(
+                    msg.reason,
+                    Some(Reason::Other("Failure was reported by ping".to_owned()))
+                );
+            }
+            _ => panic!("Expected state message"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_checker() {
+        let states = build_states();
+        let (sender, receiver) = mpsc::channel();
+
+        // Only heartbeat checkers can be pinged
+        for path in &["/ping/unknown", "/ping/unknown/fail", "/ping/web"] {
+            let response = handle(path, &states, &sender);
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", path);
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use hyper::header::AUTHORIZATION;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde_json::json;
+use url::form_urlencoded;
+
+use std::time::{Duration, SystemTime};
+
+use super::{respond, text_response};
+use crate::config::MaintenanceScope;
+use crate::maintenance::{SharedSilences, Silence};
+use crate::notifiers::format_timestamp;
+
+const PATH: &str = "/api/silences";
+
+// Silences mute alerting, so they can be managed only with `server.admin_token`,
+// that is sent as `Authorization: Bearer <token>`.
+pub fn handle(
+    req: &Request<Body>,
+    admin_token: &Option<String>,
+    silences: &SharedSilences,
+) -> Response<Body> {
+    let admin_token = match admin_token {
+        Some(token) => token,
+        None => {
+            let text = "Silences API is disabled, set `server.admin_token` to enable it";
+            return text_response(StatusCode::FORBIDDEN, text);
+        }
+    };
+    let authorized = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.strip_prefix("Bearer "))
+        == Some(admin_token.as_str());
+    if !authorized {
+        return text_response(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+
+    let path = req.uri().path();
+    match *req.method() {
+        Method::GET if path == PATH => list(silences),
+        Method::POST if path == PATH => create(req, silences),
+        Method::DELETE if path != PATH => delete(path, silences),
+        _ => text_response(StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed"),
+    }
+}
+
+pub fn is_api_path(path: &str) -> bool {
+    path == PATH || (path.starts_with(PATH) && path[PATH.len()..].starts_with('/'))
+}
+
+fn list(silences: &SharedSilences) -> Response<Body> {
+    let items: Vec<serde_json::Value> = silences
+        .lock()
+        .unwrap()
+        .active(SystemTime::now())
+        .iter()
+        .map(to_json)
+        .collect();
+    let body = json!({ "silences": items }).to_string();
+    respond(StatusCode::OK, "application/json", body)
+}
+
+// Creates a silence from query parameters, e.g.
+// `POST /api/silences?duration=30m&checker=api&tag=db&comment=Upgrade&summary=false`.
+// Without `checker` and `tag` all the checkers are silenced.
+fn create(req: &Request<Body>, silences: &SharedSilences) -> Response<Body> {
+    let mut duration: Option<Duration> = None;
+    let mut scope = MaintenanceScope::default();
+    let mut comment: Option<String> = None;
+    let mut summary = true;
+
+    let query = req.uri().query().unwrap_or("");
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            "duration" => match value.parse::<humantime::Duration>() {
+                Ok(val) => duration = Some(val.into()),
+                Err(_) => {
+                    let text = format!("Invalid duration `{}`", value);
+                    return text_response(StatusCode::BAD_REQUEST, &text);
+                }
+            },
+            "checker" => scope.checkers.push(value.into_owned()),
+            "tag" => scope.tags.push(value.into_owned()),
+            "comment" => comment = Some(value.into_owned()),
+            "summary" => match value.as_ref() {
+                "true" => summary = true,
+                "false" => summary = false,
+                _ => {
+                    let text = format!("Invalid summary `{}`, expected true or false", value);
+                    return text_response(StatusCode::BAD_REQUEST, &text);
+                }
+            },
+            _ => {
+                let text = format!("Unknown parameter `{}`", key);
+                return text_response(StatusCode::BAD_REQUEST, &text);
+            }
+        }
+    }
+
+    let duration = match duration {
+        Some(duration) => duration,
+        None => return text_response(StatusCode::BAD_REQUEST, "`duration` is missing"),
+    };
+
+    let silence = silences
+        .lock()
+        .unwrap()
+        .add(scope, duration, comment, summary);
+    info!("Silence {} is created", silence.id);
+    respond(
+        StatusCode::CREATED,
+        "application/json",
+        to_json(&silence).to_string(),
+    )
+}
+
+// Removes a silence: `DELETE /api/silences/<id>`.
+fn delete(path: &str, silences: &SharedSilences) -> Response<Body> {
+    let id = path.trim_start_matches(PATH).trim_start_matches('/');
+    let removed = match id.parse::<u64>() {
+        Ok(id) => silences.lock().unwrap().remove(id),
+        Err(_) => false,
+    };
+    if removed {
+        info!("Silence {} is removed", id);
+        text_response(StatusCode::OK, "OK")
+    } else {
+        let text = format!("Unknown silence `{}`", id);
+        text_response(StatusCode::NOT_FOUND, &text)
+    }
+}
+
+fn to_json(silence: &Silence) -> serde_json::Value {
+    json!({
+        "id": silence.id,
+        "checkers": silence.scope.checkers,
+        "tags": silence.scope.tags,
+        "until": format_timestamp(silence.until),
+        "comment": silence.comment,
+        "summary": silence.summary,
+    })
+}
@@ -0,0 +1,243 @@
+use hyper::{Body, Response, StatusCode};
+use serde_json::json;
+
+use std::time::SystemTime;
+
+use super::respond;
+use crate::notifiers::format_timestamp;
+use crate::reactor::{CheckerState, SharedStates, State};
+
+// Snapshot of the states, sorted by checker id.
+fn snapshot(states: &SharedStates) -> Vec<(String, CheckerState)> {
+    let mut items: Vec<(String, CheckerState)> = states
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, checker_state)| (id.clone(), checker_state.clone()))
+        .collect();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items
+}
+
+pub fn json(states: &SharedStates) -> Response<Body> {
+    let checkers: Vec<serde_json::Value> = snapshot(states)
+        .into_iter()
+        .map(|(id, checker_state)| {
+            json!({
+                "id": id,
+                "url": checker_state.url,
+                "state": checker_state.state.to_string(),
+                "last_check": checker_state.last_checked_at.map(format_timestamp),
+                "last_transition": checker_state.changed_at.map(format_timestamp),
+                "last_error": checker_state.last_error.map(|reason| reason.to_string()),
+                "root_cause": checker_state.root_cause,
+            })
+        })
+        .collect();
+    let body = json!({ "checkers": checkers }).to_string();
+    respond(StatusCode::OK, "application/json", body)
+}
+
+pub fn html(states: &SharedStates) -> Response<Body> {
+    let items = snapshot(states);
+    let summary = if items.iter().any(|(_, s)| s.state == State::Down) {
+        "Some systems are down"
+    } else if items.iter().any(|(_, s)| s.state == State::Degraded) {
+        "Some systems are degraded"
+    } else {
+        "All systems operational"
+    };
+
+    let mut rows = String::new();
+    for (id, checker_state) in items.iter() {
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"{state}\">{state}{}</td>\
+             <td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(id),
+            format_target(&checker_state.url),
+            checker_state
+                .root_cause
+                .as_ref()
+                .map(|id| format!(" (caused by {})", escape(id)))
+                .unwrap_or_default(),
+            format_optional_time(checker_state.last_checked_at),
+            format_optional_time(checker_state.changed_at),
+            checker_state
+                .last_error
+                .as_ref()
+                .map(|reason| escape(&reason.to_string()))
+                .unwrap_or_default(),
+            state = checker_state.state,
+        ));
+    }
+
+    let body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="30">
+<title>Hellcheck status</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 0.4em 0.8em; text-align: left; }}
+.up {{ color: #2a2; font-weight: bold; }}
+.degraded {{ color: #d80; font-weight: bold; }}
+.down {{ color: #c22; font-weight: bold; }}
+</style>
+</head>
+<body>
+<h1>{}</h1>
+<table>
+<tr><th>Checker</th><th>URL</th><th>State</th><th>Last check</th><th>Last transition</th><th>Last error</th></tr>
+{}</table>
+</body>
+</html>
+"#,
+        summary, rows
+    );
+    respond(StatusCode::OK, "text/html; charset=utf-8", body)
+}
+
+// Only URLs of HTTP checkers are links, other targets (e.g. `host:port` or a command)
+// are not something a browser can open.
+fn format_target(target: &str) -> String {
+    let lowercase = target.to_lowercase();
+    if lowercase.starts_with("http://") || lowercase.starts_with("https://") {
+        format!("<a href=\"{url}\">{url}</a>", url = escape(target))
+    } else {
+        escape(target)
+    }
+}
+
+fn format_optional_time(time: Option<SystemTime>) -> String {
+    time.map(format_timestamp).unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactor::Reason;
+    use futures::{Future, Stream};
+    use hyper::header::CONTENT_TYPE;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn build_states() -> SharedStates {
+        // 2019-02-05T03:00:00Z
+        let changed_at = UNIX_EPOCH + Duration::from_secs(1_549_335_600);
+        let checker_state = |state, url: &str| CheckerState {
+            state,
+            mismatches: 0,
+            changed_at: Some(changed_at),
+            url: url.to_owned(),
+            last_checked_at: Some(changed_at + Duration::from_secs(60)),
+            last_error: None,
+            ping_deadline: None,
+            root_cause: None,
+        };
+        let mut db = checker_state(State::Down, "db.example.com:5432");
+        db.last_error = Some(Reason::Connect("<refused>".to_owned()));
+        let mut api = checker_state(State::Down, "https://api.example.com/?a=1&b=2");
+        api.root_cause = Some("db".to_owned());
+        let web = checker_state(State::Up, "https://www.example.com/");
+
+        let states = SharedStates::default();
+        {
+            let mut map = states.lock().unwrap();
+            map.insert("web".to_owned(), web);
+            map.insert("db".to_owned(), db);
+            map.insert("api".to_owned(), api);
+        }
+        states
+    }
+
+    fn read_body(response: Response<Body>) -> String {
+        let body = response.into_body().concat2().wait().unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_json() {
+        let response = json(&build_states());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        let body: serde_json::Value = serde_json::from_str(&read_body(response)).unwrap();
+        let expected = json!({
+            "checkers": [
+                {
+                    "id": "api",
+                    "url": "https://api.example.com/?a=1&b=2",
+                    "state": "down",
+                    "last_check": "2019-02-05T03:01:00Z",
+                    "last_transition": "2019-02-05T03:00:00Z",
+                    "last_error": null,
+                    "root_cause": "db",
+                },
+                {
+                    "id": "db",
+                    "url": "db.example.com:5432",
+                    "state": "down",
+                    "last_check": "2019-02-05T03:01:00Z",
+                    "last_transition": "2019-02-05T03:00:00Z",
+                    "last_error": "Connection failed: <refused>",
+                    "root_cause": null,
+                },
+                {
+                    "id": "web",
+                    "url": "https://www.example.com/",
+                    "state": "up",
+                    "last_check": "2019-02-05T03:01:00Z",
+                    "last_transition": "2019-02-05T03:00:00Z",
+                    "last_error": null,
+                    "root_cause": null,
+                },
+            ]
+        });
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn test_html() {
+        let response = html(&build_states());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+
+        let body = read_body(response);
+        assert!(body.contains("<h1>Some systems are down</h1>"));
+        let rows: Vec<&str> = body.lines().filter(|l| l.starts_with("<tr><td>")).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0],
+            "<tr><td>api</td>\
+             <td><a href=\"https://api.example.com/?a=1&amp;b=2\">https://api.example.com/?a=1&amp;b=2</a></td>\
+             <td class=\"down\">down (caused by db)</td>\
+             <td>2019-02-05T03:01:00Z</td><td>2019-02-05T03:00:00Z</td><td></td></tr>"
+        );
+        assert!(rows[1].starts_with("<tr><td>db</td><td>db.example.com:5432</td>"));
+        assert!(rows[1].ends_with("<td>Connection failed: &lt;refused&gt;</td></tr>"));
+        assert!(rows[2].contains("<td class=\"up\">up</td>"));
+
+        let states = build_states();
+        {
+            let mut map = states.lock().unwrap();
+            let mut backup = map["web"].clone();
+            backup.url = "sh -c \"pg_dump app > /dev/null\"".to_owned();
+            map.insert("backup".to_owned(), backup);
+        }
+        let body = read_body(html(&states));
+        assert!(body
+            .contains("<tr><td>backup</td><td>sh -c &quot;pg_dump app &gt; /dev/null&quot;</td>"));
+
+        states.lock().unwrap().retain(|id, _| id == "web");
+        assert!(read_body(html(&states)).contains("<h1>All systems operational</h1>"));
+    }
+}
@@ -0,0 +1,105 @@
+use futures::future::{self, Loop};
+use hyper::rt::{Future, Stream};
+
+use crate::config::BodyAssertion;
+
+// Only the beginning of a response body is verified, so a huge (or endless) response
+// can not exhaust the memory.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+// Reads the response body up to MAX_BODY_SIZE bytes, the rest is discarded.
+pub fn read_body(body: hyper::Body) -> impl Future<Item = Vec<u8>, Error = hyper::Error> {
+    future::loop_fn((body, Vec::new()), |(body, mut buf)| {
+        body.into_future()
+            .map_err(|(err, _body)| err)
+            .map(move |(chunk_opt, body)| match chunk_opt {
+                Some(chunk) => {
+                    let len = chunk.len().min(MAX_BODY_SIZE - buf.len());
+                    buf.extend_from_slice(&chunk[..len]);
+                    if buf.len() < MAX_BODY_SIZE {
+                        Loop::Continue((body, buf))
+                    } else {
+                        Loop::Break(buf)
+                    }
+                }
+                None => Loop::Break(buf),
+            })
+    })
+}
+
+// Returns a description of the first failed assertion, if any.
+pub fn verify_body(assertions: &[BodyAssertion], body: &[u8]) -> Result<(), String> {
+    let body = String::from_utf8_lossy(body);
+
+    for assertion in assertions.iter() {
+        match assertion {
+            BodyAssertion::Contains(text) => {
+                if !body.contains(text.as_str()) {
+                    return Err(format!("Response body does not contain `{}`", text));
+                }
+            }
+            BodyAssertion::NotContains(text) => {
+                if body.contains(text.as_str()) {
+                    return Err(format!("Response body contains `{}`", text));
+                }
+            }
+            BodyAssertion::Regex(regex) => {
+                if !regex.is_match(&body) {
+                    return Err(format!("Response body does not match regex `{}`", regex));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn assertions() -> Vec<BodyAssertion> {
+        vec![
+            BodyAssertion::Contains("healthy".to_owned()),
+            BodyAssertion::NotContains("error".to_owned()),
+            BodyAssertion::Regex(Regex::new(r"version: \d+").unwrap()),
+        ]
+    }
+
+    #[test]
+    fn test_verify_body() {
+        let assertions = assertions();
+        assert_eq!(verify_body(&assertions, b"healthy, version: 42"), Ok(()));
+        assert_eq!(verify_body(&[], b""), Ok(()));
+
+        assert_eq!(
+            verify_body(&assertions, b"version: 42"),
+            Err("Response body does not contain `healthy`".to_owned())
+        );
+        assert_eq!(
+            verify_body(&assertions, b"healthy, error, version: 42"),
+            Err("Response body contains `error`".to_owned())
+        );
+        assert_eq!(
+            verify_body(&assertions, b"healthy, version: unknown"),
+            Err("Response body does not match regex `version: \\d+`".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_verify_invalid_utf8() {
+        // Invalid sequences are replaced, the rest of the body is still verified
+        let assertions = assertions();
+        assert_eq!(verify_body(&assertions, b"healthy\xff version: 1"), Ok(()));
+    }
+
+    #[test]
+    fn test_read_body() {
+        let body = hyper::Body::from(vec![b'a'; MAX_BODY_SIZE + 10]);
+        assert_eq!(read_body(body).wait().unwrap().len(), MAX_BODY_SIZE);
+
+        let body = hyper::Body::from("healthy");
+        assert_eq!(read_body(body).wait().unwrap(), b"healthy".to_vec());
+    }
+}
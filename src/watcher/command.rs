@@ -0,0 +1,242 @@
+use futures_cpupool::CpuPool;
+use hyper::rt::Future;
+
+use std::io::{self, Read};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::CommandCheckerConfig;
+use crate::reactor::Reason;
+
+// Only the beginning of stdout and stderr is reported, to keep notifications readable.
+const MAX_OUTPUT_SIZE: usize = 4096;
+
+// How often to check whether the command has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// How long to wait for the rest of the output after the command is killed by the timeout.
+const OUTPUT_GRACE: Duration = Duration::from_millis(100);
+
+// Runs the command. Exit code 0 means the service is up.
+pub fn check(
+    pool: &CpuPool,
+    config: &CommandCheckerConfig,
+    timeout: Duration,
+) -> impl Future<Item = (), Error = Reason> {
+    let config = config.clone();
+    pool.spawn_fn(move || run(&config, timeout))
+}
+
+fn run(config: &CommandCheckerConfig, timeout: Duration) -> Result<(), Reason> {
+    let mut child = Command::new(&config.command)
+        .args(config.arguments.iter())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // The command gets its own process group, so it can be killed along with its children
+        .process_group(0)
+        .spawn()
+        .map_err(|err| Reason::Other(format!("Failed to run `{}`: {}", config.command, err)))?;
+
+    // Pipes are read in separate threads, so the command never blocks on a full pipe
+    let stdout_chunks = capture(child.stdout.take());
+    let stderr_chunks = capture(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let wait_result = loop {
+        match has_exited(&child) {
+            Ok(true) => break Ok(true),
+            Ok(false) if Instant::now() >= deadline => break Ok(false),
+            Ok(false) => thread::sleep(POLL_INTERVAL),
+            Err(err) => break Err(err),
+        }
+    };
+
+    // Processes left behind by the command would keep the pipes open, so they are killed
+    // even if the command has exited. The command is not reaped yet, so its pid (which is
+    // the id of the group) can not be taken by another process in the meantime.
+    kill_process_group(&child);
+    let status = child.wait();
+
+    // Processes, that have left the group (e.g. with `setsid`), may still hold the pipes open,
+    // so the output is collected only until the deadline.
+    let until = deadline.max(Instant::now() + OUTPUT_GRACE);
+    let stdout = collect(&stdout_chunks, until);
+    let stderr = collect(&stderr_chunks, until);
+    let output = format_output(&stdout, &stderr);
+
+    let status = match (wait_result, status) {
+        (Ok(false), _) if output.is_empty() => return Err(Reason::Timeout(timeout)),
+        (Ok(false), _) => {
+            let message = format!("{}{}", Reason::Timeout(timeout), output);
+            return Err(Reason::Other(message));
+        }
+        (Ok(true), Ok(status)) => status,
+        (Err(err), _) | (_, Err(err)) => {
+            let message = format!("Failed to wait for `{}`: {}", config.command, err);
+            return Err(Reason::Other(message));
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    let message = match status.code() {
+        Some(code) => format!("Command exited with code {}", code),
+        None => format!(
+            "Command was killed by signal {}",
+            status.signal().unwrap_or_default()
+        ),
+    };
+    Err(Reason::Other(format!("{}{}", message, output)))
+}
+
+// Reads the pipe to the end in a separate thread and sends what is read in chunks.
+// Once MAX_OUTPUT_SIZE is exceeded, the rest is read, but not sent.
+fn capture<R: Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut pipe = match pipe {
+            Some(pipe) => pipe,
+            None => return,
+        };
+        let mut sent = 0;
+        let mut buf = [0u8; 1024];
+        loop {
+            match pipe.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) if sent <= MAX_OUTPUT_SIZE => {
+                    sent += n;
+                    if sender.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+            }
+        }
+    });
+    receiver
+}
+
+// Receives the output until the pipe is closed or until the given time,
+// keeping at most MAX_OUTPUT_SIZE bytes.
+fn collect(chunks: &mpsc::Receiver<Vec<u8>>, until: Instant) -> String {
+    let mut output: Vec<u8> = vec![];
+    while let Ok(chunk) = chunks.recv_timeout(until.saturating_duration_since(Instant::now())) {
+        output.extend(chunk);
+    }
+
+    let truncated = output.len() > MAX_OUTPUT_SIZE;
+    output.truncate(MAX_OUTPUT_SIZE);
+    let mut text = String::from_utf8_lossy(&output).trim().to_owned();
+    if truncated {
+        text.push_str("... (truncated)");
+    }
+    text
+}
+
+fn format_output(stdout: &str, stderr: &str) -> String {
+    let mut output = String::new();
+    if !stdout.is_empty() {
+        output.push_str(&format!("\nstdout: {}", stdout));
+    }
+    if !stderr.is_empty() {
+        output.push_str(&format!("\nstderr: {}", stderr));
+    }
+    output
+}
+
+// Unlike `Child::try_wait` does not reap the process, so it stays a zombie until `Child::wait`.
+fn has_exited(child: &Child) -> io::Result<bool> {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let options = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+    let result = unsafe { libc::waitid(libc::P_PID, child.id(), &mut info, options) };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // With WNOHANG si_pid stays 0, if the process is still running
+    Ok(unsafe { info.si_pid() } != 0)
+}
+
+fn kill_process_group(child: &Child) {
+    // Negative pid refers to the process group. It's fine if the group does not exist anymore.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> CommandCheckerConfig {
+        CommandCheckerConfig {
+            command: "sh".to_owned(),
+            arguments: vec!["-c".to_owned(), script.to_owned()],
+        }
+    }
+
+    fn message(result: Result<(), Reason>) -> String {
+        match result {
+            Err(Reason::Other(message)) => message,
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exit_status() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(run(&sh("exit 0"), timeout), Ok(()));
+        assert_eq!(
+            message(run(&sh("echo out; echo err >&2; exit 3"), timeout)),
+            "Command exited with code 3\nstdout: out\nstderr: err"
+        );
+        assert_eq!(
+            message(run(&sh("kill -9 $$"), timeout)),
+            "Command was killed by signal 9"
+        );
+
+        let config = CommandCheckerConfig {
+            command: "/nonexistent/command".to_owned(),
+            arguments: vec![],
+        };
+        assert!(message(run(&config, timeout)).starts_with("Failed to run `/nonexistent/command`"));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let timeout = Duration::from_millis(300);
+        let started_at = Instant::now();
+        assert_eq!(run(&sh("sleep 30"), timeout), Err(Reason::Timeout(timeout)));
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+
+        // The output collected before the timeout is reported
+        assert_eq!(
+            message(run(&sh("echo started; sleep 30"), timeout)),
+            "Timed out after 300ms\nstdout: started"
+        );
+    }
+
+    #[test]
+    fn test_process_out_of_group() {
+        // The detached process holds the pipes open, but the command has succeeded
+        let timeout = Duration::from_millis(500);
+        let started_at = Instant::now();
+        assert_eq!(run(&sh("setsid sleep 5 & exit 0"), timeout), Ok(()));
+        assert!(started_at.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_output_truncation() {
+        let script = "head -c 10000 /dev/zero | tr '\\0' a; exit 1";
+        let message = message(run(&sh(script), Duration::from_secs(5)));
+        let expected = format!(
+            "Command exited with code 1\nstdout: {}... (truncated)",
+            "a".repeat(MAX_OUTPUT_SIZE)
+        );
+        assert_eq!(message, expected);
+    }
+}
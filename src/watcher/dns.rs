@@ -0,0 +1,461 @@
+// Minimal DNS client: sends a single query over UDP (falling back to TCP if the response is
+// truncated) and extracts answers of the requested type.
+
+use futures_cpupool::CpuPool;
+use hyper::rt::Future;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::{DnsCheckerConfig, DnsRecordType};
+use crate::reactor::Reason;
+
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const DNS_PORT: u16 = 53;
+
+// Classic limit for DNS messages over UDP without EDNS.
+const MAX_UDP_SIZE: usize = 512;
+
+const CLASS_IN: u16 = 1;
+const RCODE_NXDOMAIN: u8 = 3;
+
+// Limits the number of compression pointers to follow, so a malicious response can't loop.
+const MAX_POINTER_JUMPS: usize = 64;
+
+// Resolves the name and verifies that all the expected values are present among the answers.
+pub fn check(
+    pool: &CpuPool,
+    config: &DnsCheckerConfig,
+    timeout: Duration,
+) -> impl Future<Item = (), Error = Reason> {
+    let config = config.clone();
+    pool.spawn_fn(move || {
+        let nameserver = match config.nameserver {
+            Some(addr) => addr,
+            None => system_nameserver(),
+        };
+        let answers = resolve(nameserver, &config.name, config.record_type, timeout)?;
+        verify_answers(&config, &answers)
+    })
+}
+
+fn verify_answers(config: &DnsCheckerConfig, answers: &[String]) -> Result<(), Reason> {
+    if answers.is_empty() {
+        let message = format!(
+            "No {} records found for {}",
+            config.record_type, config.name
+        );
+        return Err(Reason::Dns(message));
+    }
+
+    for expected in config.expected.iter() {
+        let expected = normalize(config.record_type, expected);
+        if !answers.contains(&expected) {
+            let message = format!(
+                "Expected {} record `{}` for {}, got: {}",
+                config.record_type,
+                expected,
+                config.name,
+                answers.join(", ")
+            );
+            return Err(Reason::Assertion(message));
+        }
+    }
+
+    Ok(())
+}
+
+// Brings a value to the same form as answers are formatted, so they can be compared.
+fn normalize(record_type: DnsRecordType, value: &str) -> String {
+    match record_type {
+        DnsRecordType::A | DnsRecordType::Aaaa => match value.parse::<IpAddr>() {
+            Ok(ip) => ip.to_string(),
+            Err(_) => value.to_owned(),
+        },
+        DnsRecordType::Cname | DnsRecordType::Mx => value.trim_end_matches('.').to_lowercase(),
+        DnsRecordType::Txt => value.to_owned(),
+    }
+}
+
+// The first nameserver from /etc/resolv.conf, or the local one if there are none.
+fn system_nameserver() -> SocketAddr {
+    let content = fs::read_to_string(RESOLV_CONF).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("nameserver") => parts.next(),
+                _ => None,
+            }
+        })
+        // Zone index of IPv6 address (e.g. `fe80::1%eth0`) is not supported
+        .filter_map(|addr| addr.parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, DNS_PORT))
+        .next()
+        .unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DNS_PORT))
+}
+
+fn resolve(
+    nameserver: SocketAddr,
+    name: &str,
+    record_type: DnsRecordType,
+    timeout: Duration,
+) -> Result<Vec<String>, Reason> {
+    let id = query_id();
+    let query = build_query(id, name, record_type.code()).map_err(Reason::Dns)?;
+
+    let mut response = query_udp(nameserver, &query, id, timeout)
+        .map_err(|err| Reason::Dns(format!("Query to {} failed: {}", nameserver, err)))?;
+    if response.truncated {
+        response = query_tcp(nameserver, &query, id, timeout)
+            .map_err(|err| Reason::Dns(format!("Query to {} failed: {}", nameserver, err)))?;
+    }
+
+    match response.rcode {
+        0 => {}
+        RCODE_NXDOMAIN => {
+            return Err(Reason::Dns(format!("{} does not exist (NXDOMAIN)", name)));
+        }
+        rcode => {
+            let message = format!("{} responded with error code {}", nameserver, rcode);
+            return Err(Reason::Dns(message));
+        }
+    }
+
+    let answers = response
+        .answers
+        .into_iter()
+        .filter(|record| record.record_type == record_type.code())
+        .map(|record| record.value)
+        .collect();
+    Ok(answers)
+}
+
+// Pseudo random id of a query. It does not have to be unpredictable, because the checker
+// only talks to the configured nameserver.
+fn query_id() -> u16 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    (nanos ^ (nanos >> 16) ^ std::process::id()) as u16
+}
+
+fn query_udp(
+    nameserver: SocketAddr,
+    query: &[u8],
+    id: u16,
+    timeout: Duration,
+) -> Result<Response, String> {
+    let local_addr: SocketAddr = match nameserver {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local_addr).map_err(|err| err.to_string())?;
+    socket.connect(nameserver).map_err(|err| err.to_string())?;
+    socket.send(query).map_err(|err| err.to_string())?;
+
+    // Responses to other (e.g. previously timed out) queries are skipped
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; MAX_UDP_SIZE];
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err("timed out".to_owned());
+        }
+        socket
+            .set_read_timeout(Some(deadline - now))
+            .map_err(|err| err.to_string())?;
+        let len = socket.recv(&mut buf).map_err(|err| err.to_string())?;
+        match parse_response(id, &buf[..len]) {
+            Ok(Some(response)) => return Ok(response),
+            Ok(None) => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// Messages over TCP are prefixed with two bytes of length.
+fn query_tcp(
+    nameserver: SocketAddr,
+    query: &[u8],
+    id: u16,
+    timeout: Duration,
+) -> Result<Response, String> {
+    let mut stream =
+        TcpStream::connect_timeout(&nameserver, timeout).map_err(|err| err.to_string())?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|()| stream.set_write_timeout(Some(timeout)))
+        .map_err(|err| err.to_string())?;
+
+    let mut msg = (query.len() as u16).to_be_bytes().to_vec();
+    msg.extend_from_slice(query);
+    stream.write_all(&msg).map_err(|err| err.to_string())?;
+
+    let mut len_buf = [0u8; 2];
+    stream
+        .read_exact(&mut len_buf)
+        .map_err(|err| err.to_string())?;
+    let mut buf = vec![0u8; u16::from_be_bytes(len_buf) as usize];
+    stream.read_exact(&mut buf).map_err(|err| err.to_string())?;
+
+    match parse_response(id, &buf)? {
+        Some(response) => Ok(response),
+        None => Err("response does not match the query".to_owned()),
+    }
+}
+
+fn build_query(id: u16, name: &str, record_type: u16) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::with_capacity(MAX_UDP_SIZE);
+    buf.extend_from_slice(&id.to_be_bytes());
+    // Flags: standard query, recursion desired
+    buf.extend_from_slice(&[0x01, 0x00]);
+    // One question, no answer, authority and additional records
+    buf.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid domain name `{}`", name));
+        }
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+
+    buf.extend_from_slice(&record_type.to_be_bytes());
+    buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(buf)
+}
+
+#[derive(Debug)]
+struct Response {
+    rcode: u8,
+    truncated: bool,
+    answers: Vec<Record>,
+}
+
+#[derive(Debug)]
+struct Record {
+    record_type: u16,
+    value: String,
+}
+
+// Returns None if the message is not a response to the query with the given id.
+fn parse_response(id: u16, msg: &[u8]) -> Result<Option<Response>, String> {
+    if msg.len() < 12 {
+        return Err("response is too short".to_owned());
+    }
+    let is_response = msg[2] & 0x80 != 0;
+    if read_u16(msg, 0)? != id || !is_response {
+        return Ok(None);
+    }
+
+    let truncated = msg[2] & 0x02 != 0;
+    let rcode = msg[3] & 0x0f;
+    let question_count = read_u16(msg, 4)?;
+    let answer_count = read_u16(msg, 6)?;
+
+    let mut pos = 12;
+    for _ in 0..question_count {
+        let (_, next) = read_name(msg, pos)?;
+        // Type and class
+        pos = next + 4;
+    }
+
+    let mut answers = vec![];
+    for _ in 0..answer_count {
+        let (_, next) = read_name(msg, pos)?;
+        let record_type = read_u16(msg, next)?;
+        // Class (2 bytes) and TTL (4 bytes) are not interesting
+        let data_len = read_u16(msg, next + 8)? as usize;
+        let data_pos = next + 10;
+        if data_pos + data_len > msg.len() {
+            return Err("response is truncated".to_owned());
+        }
+        if let Some(value) = parse_record_data(msg, record_type, data_pos, data_len)? {
+            answers.push(Record { record_type, value });
+        }
+        pos = data_pos + data_len;
+    }
+
+    Ok(Some(Response {
+        rcode,
+        truncated,
+        answers,
+    }))
+}
+
+// Formats data of the supported record types. Other types are skipped.
+fn parse_record_data(
+    msg: &[u8],
+    record_type: u16,
+    pos: usize,
+    len: usize,
+) -> Result<Option<String>, String> {
+    let data = &msg[pos..pos + len];
+    let value = if record_type == DnsRecordType::A.code() && len == 4 {
+        Ipv4Addr::new(data[0], data[1], data[2], data[3]).to_string()
+    } else if record_type == DnsRecordType::Aaaa.code() && len == 16 {
+        let mut octets = [0u8; 16];
+        octets.copy_from_slice(data);
+        Ipv6Addr::from(octets).to_string()
+    } else if record_type == DnsRecordType::Cname.code() {
+        read_name(msg, pos)?.0
+    } else if record_type == DnsRecordType::Mx.code() {
+        let preference = read_u16(msg, pos)?;
+        let (exchange, _) = read_name(msg, pos + 2)?;
+        format!("{} {}", preference, exchange)
+    } else if record_type == DnsRecordType::Txt.code() {
+        // TXT record consists of one or more length prefixed strings
+        let mut text = vec![];
+        let mut i = 0;
+        while i < data.len() {
+            let chunk_len = data[i] as usize;
+            let chunk_end = (i + 1 + chunk_len).min(data.len());
+            text.extend_from_slice(&data[i + 1..chunk_end]);
+            i = chunk_end;
+        }
+        String::from_utf8_lossy(&text).into_owned()
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(value))
+}
+
+// Reads a possibly compressed domain name. Returns the name in lower case without
+// the trailing dot and the position right after the name.
+fn read_name(msg: &[u8], start: usize) -> Result<(String, usize), String> {
+    let mut labels: Vec<String> = vec![];
+    let mut pos = start;
+    let mut end: Option<usize> = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *msg.get(pos).ok_or("response is truncated")? as usize;
+        if len & 0xc0 == 0xc0 {
+            // Compression pointer to a name that appeared earlier in the message
+            let pointer = (read_u16(msg, pos)? & 0x3fff) as usize;
+            if end.is_none() {
+                end = Some(pos + 2);
+            }
+            jumps += 1;
+            if jumps > MAX_POINTER_JUMPS {
+                return Err("too many compression pointers in response".to_owned());
+            }
+            pos = pointer;
+        } else if len == 0 {
+            let name = labels.join(".").to_lowercase();
+            return Ok((name, end.unwrap_or(pos + 1)));
+        } else {
+            let label = msg
+                .get(pos + 1..pos + 1 + len)
+                .ok_or("response is truncated")?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            pos += 1 + len;
+        }
+    }
+}
+
+fn read_u16(msg: &[u8], pos: usize) -> Result<u16, String> {
+    match msg.get(pos..pos + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err("response is truncated".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    // Answers a single query with the given records of the requested type,
+    // using a compression pointer to the question name.
+    fn spawn_stub_server(rcode: u8, records: Vec<Vec<u8>>) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; MAX_UDP_SIZE];
+            let (len, peer) = socket.recv_from(&mut buf).unwrap();
+            let query = &buf[..len];
+            let record_type = &query[len - 4..len - 2];
+
+            let mut resp = query.to_vec();
+            resp[2] = 0x81;
+            resp[3] = 0x80 | rcode;
+            resp[7] = records.len() as u8;
+            for data in records.iter() {
+                resp.extend_from_slice(&[0xc0, 12]);
+                resp.extend_from_slice(record_type);
+                resp.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+                resp.extend_from_slice(&(data.len() as u16).to_be_bytes());
+                resp.extend_from_slice(data);
+            }
+            socket.send_to(&resp, peer).unwrap();
+        });
+
+        addr
+    }
+
+    fn config(record_type: DnsRecordType, nameserver: SocketAddr) -> DnsCheckerConfig {
+        DnsCheckerConfig {
+            name: "example.com".to_owned(),
+            record_type,
+            nameserver: Some(nameserver),
+            expected: vec![],
+        }
+    }
+
+    fn run_check(config: &DnsCheckerConfig) -> Result<(), Reason> {
+        let pool = CpuPool::new(1);
+        check(&pool, config, Duration::from_secs(2)).wait()
+    }
+
+    #[test]
+    fn test_a_record_matches_expected() {
+        let nameserver = spawn_stub_server(0, vec![vec![93, 184, 216, 34], vec![10, 0, 0, 1]]);
+        let mut config = config(DnsRecordType::A, nameserver);
+        config.expected = vec!["10.0.0.1".to_owned()];
+        assert_eq!(run_check(&config), Ok(()));
+    }
+
+    #[test]
+    fn test_a_record_does_not_match_expected() {
+        let nameserver = spawn_stub_server(0, vec![vec![93, 184, 216, 34]]);
+        let mut config = config(DnsRecordType::A, nameserver);
+        config.expected = vec!["10.0.0.1".to_owned()];
+        assert_eq!(
+            run_check(&config),
+            Err(Reason::Assertion(
+                "Expected A record `10.0.0.1` for example.com, got: 93.184.216.34".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_mx_record() {
+        // Preference 10, exchange `mail.` followed by a pointer to `example.com`
+        let data = vec![0, 10, 4, b'm', b'a', b'i', b'l', 0xc0, 12];
+        let nameserver = spawn_stub_server(0, vec![data]);
+        let mut config = config(DnsRecordType::Mx, nameserver);
+        config.expected = vec!["10 Mail.Example.com.".to_owned()];
+        assert_eq!(run_check(&config), Ok(()));
+    }
+
+    #[test]
+    fn test_nxdomain() {
+        let nameserver = spawn_stub_server(RCODE_NXDOMAIN, vec![]);
+        let config = config(DnsRecordType::Txt, nameserver);
+        assert_eq!(
+            run_check(&config),
+            Err(Reason::Dns(
+                "example.com does not exist (NXDOMAIN)".to_owned()
+            ))
+        );
+    }
+}
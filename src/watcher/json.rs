@@ -0,0 +1,139 @@
+use serde_json::Value as JsonValue;
+
+use crate::config::{JsonAssertion, JsonOperator};
+
+// Returns a description of the first failed assertion, if any.
+pub fn verify_json(assertions: &[JsonAssertion], body: &[u8]) -> Result<(), String> {
+    let json: JsonValue = serde_json::from_slice(body)
+        .map_err(|err| format!("Response body is not a valid JSON: {}", err))?;
+
+    for assertion in assertions.iter() {
+        let actual = match json.pointer(&assertion.pointer) {
+            Some(value) => value,
+            None => return Err(format!("JSON value at `{}` is missing", assertion.pointer)),
+        };
+
+        if !matches(&assertion.operator, actual) {
+            return Err(format!(
+                "JSON value at `{}` is {}, expected {}",
+                assertion.pointer,
+                actual,
+                describe_operator(&assertion.operator)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn matches(operator: &JsonOperator, actual: &JsonValue) -> bool {
+    match operator {
+        JsonOperator::Equals(expected) => json_eq(actual, expected),
+        JsonOperator::NotEquals(expected) => !json_eq(actual, expected),
+        JsonOperator::GreaterThan(num) => compare(actual, |v| v > *num),
+        JsonOperator::GreaterOrEqual(num) => compare(actual, |v| v >= *num),
+        JsonOperator::LessThan(num) => compare(actual, |v| v < *num),
+        JsonOperator::LessOrEqual(num) => compare(actual, |v| v <= *num),
+    }
+}
+
+// Only numbers can be compared.
+fn compare<F: Fn(f64) -> bool>(actual: &JsonValue, f: F) -> bool {
+    match actual.as_f64() {
+        Some(value) => f(value),
+        None => false,
+    }
+}
+
+// Numbers are compared by value, so `1` is equal to `1.0`.
+fn json_eq(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+fn describe_operator(operator: &JsonOperator) -> String {
+    match operator {
+        JsonOperator::Equals(expected) => format!("{}", expected),
+        JsonOperator::NotEquals(expected) => format!("not {}", expected),
+        JsonOperator::GreaterThan(num) => format!("> {}", num),
+        JsonOperator::GreaterOrEqual(num) => format!(">= {}", num),
+        JsonOperator::LessThan(num) => format!("< {}", num),
+        JsonOperator::LessOrEqual(num) => format!("<= {}", num),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assertion(pointer: &str, operator: JsonOperator) -> JsonAssertion {
+        JsonAssertion {
+            pointer: pointer.to_owned(),
+            operator,
+        }
+    }
+
+    const BODY: &[u8] = br#"{"status": "ok", "db": {"connections": 5, "lag": 1.5}, "errors": []}"#;
+
+    #[test]
+    fn test_verify_json() {
+        let assertions = vec![
+            assertion("/status", JsonOperator::Equals(json!("ok"))),
+            assertion("/errors", JsonOperator::Equals(json!([]))),
+            assertion("/db/connections", JsonOperator::Equals(json!(5.0))),
+            assertion("/db/connections", JsonOperator::NotEquals(json!(0))),
+            assertion("/db/connections", JsonOperator::GreaterThan(4.0)),
+            assertion("/db/connections", JsonOperator::GreaterOrEqual(5.0)),
+            assertion("/db/lag", JsonOperator::LessThan(2.0)),
+            assertion("/db/lag", JsonOperator::LessOrEqual(1.5)),
+        ];
+        assert_eq!(verify_json(&assertions, BODY), Ok(()));
+    }
+
+    #[test]
+    fn test_failure_reasons() {
+        let cases = vec![
+            (
+                assertion("/status", JsonOperator::Equals(json!("fail"))),
+                r#"JSON value at `/status` is "ok", expected "fail""#,
+            ),
+            (
+                assertion("/status", JsonOperator::NotEquals(json!("ok"))),
+                r#"JSON value at `/status` is "ok", expected not "ok""#,
+            ),
+            (
+                assertion("/db/connections", JsonOperator::GreaterThan(5.0)),
+                "JSON value at `/db/connections` is 5, expected > 5",
+            ),
+            (
+                assertion("/db/lag", JsonOperator::LessOrEqual(1.0)),
+                "JSON value at `/db/lag` is 1.5, expected <= 1",
+            ),
+            // Only numbers can be compared
+            (
+                assertion("/status", JsonOperator::LessThan(10.0)),
+                r#"JSON value at `/status` is "ok", expected < 10"#,
+            ),
+            (
+                assertion("/db/replicas", JsonOperator::Equals(json!(2))),
+                "JSON value at `/db/replicas` is missing",
+            ),
+        ];
+        for (assertion, expected) in cases {
+            assert_eq!(verify_json(&[assertion], BODY), Err(expected.to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_not_json() {
+        let err = verify_json(&[], b"<html>OK</html>").unwrap_err();
+        assert!(
+            err.starts_with("Response body is not a valid JSON: "),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,519 @@
+use futures::future::{self, Either};
+use futures::sync::oneshot;
+use futures_cpupool::CpuPool;
+use hyper::header::{HeaderValue, AUTHORIZATION};
+use hyper::rt::{Future, Stream};
+use hyper::Client;
+use tokio_core::reactor::Handle;
+use tokio_timer::{timeout, Timeout};
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::{Auth, BasicAuth, CheckerConfig, CheckerKind, FileConfig, HttpCheckerConfig};
+use crate::metrics::Metrics;
+use crate::reactor::{Message, Reason, State, StateMessage};
+
+mod body;
+mod command;
+mod dns;
+mod json;
+mod oauth2;
+mod redirect;
+mod resolver;
+#[cfg(test)]
+mod stub;
+mod tcp;
+mod tls;
+
+use self::oauth2::TokenCache;
+
+type HttpsClient = hyper::Client<resolver::Connector>;
+
+// Resolves into details of the probe and result of the verification.
+type Probe = Box<dyn Future<Item = (ProbeDetails, Result<(), Reason>), Error = Reason>>;
+
+// Number of threads for probes, that can be done only with blocking calls.
+// Every such probe occupies a thread until it completes or times out.
+const BLOCKING_THREADS: usize = 16;
+
+// Command checker enforces the timeout itself, so it can kill the process.
+// This is how much longer the checker waits for the command checker to report the timeout.
+const COMMAND_KILL_GRACE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub state: State,
+    pub reason: Option<Reason>,
+    pub http_status: Option<u16>,
+    // Not set if the service has not responded
+    pub response_time: Option<Duration>,
+    pub certificate_days_left: Option<i64>,
+}
+
+// Diagnostics collected by a probe, that are reported along with the result.
+#[derive(Debug, Default)]
+struct ProbeDetails {
+    // Set if an HTTP response was received
+    http_status: Option<u16>,
+    certificate_days_left: Option<i64>,
+}
+
+// Resources shared by all the checks.
+#[derive(Clone)]
+struct Context {
+    client: HttpsClient,
+    tokens: TokenCache,
+    pool: CpuPool,
+}
+
+impl Context {
+    fn new() -> Self {
+        Self {
+            client: build_client(),
+            tokens: TokenCache::default(),
+            pool: CpuPool::new(BLOCKING_THREADS),
+        }
+    }
+}
+
+// Runs checkers forever. Every config received from `reloads` replaces the running checkers.
+pub fn run(
+    config: FileConfig,
+    sender: mpsc::Sender<Message>,
+    reloads: futures::sync::mpsc::UnboundedReceiver<FileConfig>,
+    metrics: Metrics,
+) {
+    let mut core = tokio_core::reactor::Core::new().unwrap();
+    let handle = core.handle();
+    let context = Context::new();
+
+    let mut cancel_handles = spawn_checkers(&handle, &config, &sender, &context, &metrics);
+
+    let f = reloads.for_each(move |config| {
+        info!("Rescheduling checkers");
+
+        // Dropping the cancel handles stops the running checkers
+        cancel_handles.clear();
+        cancel_handles = spawn_checkers(&handle, &config, &sender, &context, &metrics);
+        Ok(())
+    });
+
+    // Run core until the reloads channel is closed
+    let res = core.run(f);
+
+    match res {
+        Ok(_) => {}
+        Err(_) => {
+            eprintln!("ERROR: looks likes hellcheck crashed");
+            std::process::exit(1);
+        }
+    }
+}
+
+// Runs every polled checker exactly once and returns the results in the same order as checkers
+// are declared in the config.
+pub fn check_once(config: &FileConfig) -> Vec<CheckResult> {
+    let context = Context::new();
+    let checks_futures = config
+        .checkers
+        .iter()
+        .filter(|c| c.is_polled())
+        .map(|c| check::<()>(&context, c));
+    let f = futures::future::join_all(checks_futures);
+
+    let mut core = tokio_core::reactor::Core::new().unwrap();
+
+    // unwrap is safe here, because a single check never fails, it reports State::Down instead.
+    core.run(f).unwrap()
+}
+
+fn spawn_checkers(
+    handle: &Handle,
+    config: &FileConfig,
+    sender: &mpsc::Sender<Message>,
+    context: &Context,
+    metrics: &Metrics,
+) -> Vec<oneshot::Sender<()>> {
+    let mut cancel_handles = vec![];
+
+    for checker in config.checkers.iter().filter(|c| c.is_polled()) {
+        let (cancel_sender, cancel_receiver) = oneshot::channel::<()>();
+        let f = build_checker_future(
+            checker.clone(),
+            sender.clone(),
+            context.clone(),
+            metrics.clone(),
+        )
+        .map_err(|_| {
+            eprintln!("ERROR: looks likes hellcheck crashed");
+            std::process::exit(1);
+        })
+        .select2(cancel_receiver)
+        .then(|_| Ok(()));
+        handle.spawn(f);
+        cancel_handles.push(cancel_sender);
+    }
+
+    cancel_handles
+}
+
+fn build_checker_future(
+    service: CheckerConfig,
+    sender: mpsc::Sender<Message>,
+    context: Context,
+    metrics: Metrics,
+) -> impl Future<Item = (), Error = tokio_timer::Error> {
+    let stream = tokio_timer::Interval::new(Instant::now(), service.interval);
+
+    stream.for_each(move |_| {
+        let checker_id = service.id.clone();
+        let sender = sender.clone();
+        let metrics = metrics.clone();
+
+        info!("Checking {}", service.target());
+
+        check(&context, &service).map(move |result| {
+            metrics.record_check(
+                &checker_id,
+                &result.state,
+                result.http_status,
+                result.response_time,
+            );
+            let msg = StateMessage {
+                checker_id,
+                state: result.state,
+                reason: result.reason,
+                http_status: result.http_status,
+                response_time: result.response_time,
+                certificate_days_left: result.certificate_days_left,
+                timestamp: SystemTime::now(),
+            };
+            sender.send(Message::State(msg)).unwrap();
+        })
+    })
+}
+
+fn check<E>(
+    context: &Context,
+    service: &CheckerConfig,
+) -> impl Future<Item = CheckResult, Error = E> {
+    let started_at = Instant::now();
+    let timeout = service.timeout;
+    let warn_latency = service.warn_latency;
+    let max_latency = service.max_latency;
+
+    let f: Probe = match service.kind {
+        CheckerKind::Http(ref http) => Box::new(check_http(context, http)),
+        CheckerKind::Tcp(ref tcp) => Box::new(
+            tcp::check(&context.pool, tcp, timeout).map(|()| (ProbeDetails::default(), Ok(()))),
+        ),
+        CheckerKind::Dns(ref dns) => Box::new(
+            dns::check(&context.pool, dns, timeout).map(|()| (ProbeDetails::default(), Ok(()))),
+        ),
+        CheckerKind::Tls(ref tls) => Box::new(tls::check(&context.pool, tls, timeout).map(
+            |(certificate_days_left, verification)| {
+                let details = ProbeDetails {
+                    certificate_days_left,
+                    ..ProbeDetails::default()
+                };
+                (details, verification)
+            },
+        )),
+        CheckerKind::Command(ref command) => Box::new(
+            command::check(&context.pool, command, timeout)
+                .map(|()| (ProbeDetails::default(), Ok(()))),
+        ),
+        CheckerKind::Heartbeat(_) => Box::new(future::err(Reason::Other(
+            "Heartbeat checkers are not polled".to_owned(),
+        ))),
+    };
+
+    let future_timeout = match service.kind {
+        CheckerKind::Command(_) => timeout + COMMAND_KILL_GRACE,
+        _ => timeout,
+    };
+
+    Timeout::new(f, future_timeout).then(move |r| {
+        let response_time = started_at.elapsed();
+        // A probe fails if it gets no response, e.g. because of a timeout or a connection error
+        let responded = r.is_ok();
+        let (details, verification) = match r {
+            Ok((details, verification)) => (details, verification),
+            Err(err) => (
+                ProbeDetails::default(),
+                Err(timeout_error_to_reason(err, timeout)),
+            ),
+        };
+        let (state, reason) = match verification {
+            Ok(()) => verify_latency(response_time, warn_latency, max_latency),
+            Err(reason) => (State::Down, Some(reason)),
+        };
+
+        Ok(CheckResult {
+            state,
+            reason,
+            http_status: details.http_status,
+            response_time: if responded { Some(response_time) } else { None },
+            certificate_days_left: details.certificate_days_left,
+        })
+    })
+}
+
+// Healthy, but slow service is degraded or even down.
+fn verify_latency(
+    response_time: Duration,
+    warn_latency: Option<Duration>,
+    max_latency: Option<Duration>,
+) -> (State, Option<Reason>) {
+    match (warn_latency, max_latency) {
+        (_, Some(max)) if response_time > max => {
+            (State::Down, Some(Reason::Latency(response_time, max)))
+        }
+        (Some(warn), _) if response_time > warn => {
+            (State::Degraded, Some(Reason::Latency(response_time, warn)))
+        }
+        _ => (State::Up, None),
+    }
+}
+
+fn check_http(
+    context: &Context,
+    service: &HttpCheckerConfig,
+) -> impl Future<Item = (ProbeDetails, Result<(), Reason>), Error = Reason> {
+    let expected_status = service.expected_status.clone();
+    let body_assertions = service.body_assertions.clone();
+    let json_assertions = service.json_assertions.clone();
+    let final_url = service.final_url.clone();
+
+    let oauth2 = match service.auth {
+        Some(Auth::OAuth2(ref oauth2)) => Some(oauth2.clone()),
+        _ => None,
+    };
+
+    // OAuth2 access token has to be obtained before the request can be sent
+    let authorized_req = match (build_request(service), &oauth2) {
+        (Ok(req), Some(oauth2)) => {
+            let f = context
+                .tokens
+                .access_token(&context.client, oauth2)
+                .and_then(|token| authorize_with_token(req, &token));
+            Either::A(f)
+        }
+        (req, _) => Either::B(future::result(req)),
+    };
+
+    let client = context.client.clone();
+    let tokens = context.tokens.clone();
+    let service = service.clone();
+
+    authorized_req
+        .and_then(move |req| redirect::send(&client, &service, req))
+        .and_then(move |(resp, chain)| {
+            let status = resp.status().as_u16();
+
+            // The token might have been revoked, so a new one must be obtained next time
+            if status == 401 {
+                if let Some(ref oauth2) = oauth2 {
+                    tokens.invalidate(oauth2);
+                }
+            }
+
+            if let Some(ref final_url) = final_url {
+                if let Err(reason) = redirect::verify_final_url(final_url, &chain) {
+                    return Either::A(future::ok((status, Err(reason))));
+                }
+            }
+
+            if !expected_status.iter().any(|range| range.contains(status)) {
+                let reason = Reason::UnexpectedStatus(status);
+                return Either::A(future::ok((status, Err(reason))));
+            }
+
+            // Do not download the body, if nobody is interested in it
+            if body_assertions.is_empty() && json_assertions.is_empty() {
+                return Either::A(future::ok((status, Ok(()))));
+            }
+
+            let f = body::read_body(resp.into_body())
+                .map_err(|err| hyper_error_to_reason(&err))
+                .map(move |body| {
+                    let verification = body::verify_body(&body_assertions, &body)
+                        .and_then(|()| {
+                            if json_assertions.is_empty() {
+                                Ok(())
+                            } else {
+                                json::verify_json(&json_assertions, &body)
+                            }
+                        })
+                        .map_err(Reason::Assertion);
+                    (status, verification)
+                });
+            Either::B(f)
+        })
+        .map(|(status, verification)| {
+            let details = ProbeDetails {
+                http_status: Some(status),
+                ..ProbeDetails::default()
+            };
+            (details, verification)
+        })
+}
+
+fn timeout_error_to_reason(err: timeout::Error<Reason>, timeout: Duration) -> Reason {
+    if err.is_elapsed() {
+        Reason::Timeout(timeout)
+    } else if err.is_timer() {
+        Reason::Other(format!("Timer error: {}", err))
+    } else {
+        // unwrap is safe here, because it's neither elapsed nor timer error
+        err.into_inner().unwrap()
+    }
+}
+
+// Classifies hyper error by looking at its cause. Errors of TLS handshake and DNS resolution
+// are wrapped into io::Error by hyper-tls and by the resolver respectively.
+fn hyper_error_to_reason(err: &hyper::Error) -> Reason {
+    if !err.is_connect() {
+        return Reason::Other(err.to_string());
+    }
+
+    let io_err = err
+        .cause2()
+        .and_then(|cause| cause.downcast_ref::<std::io::Error>());
+    let description = match io_err {
+        Some(io_err) => io_err.to_string(),
+        None => err.to_string(),
+    };
+
+    match io_err.and_then(|e| e.get_ref()) {
+        Some(inner) if inner.is::<hyper_tls::Error>() => Reason::Tls(inner.to_string()),
+        Some(inner) if inner.is::<resolver::ResolveError>() => Reason::Dns(description),
+        _ => Reason::Connect(description),
+    }
+}
+
+fn build_request(service: &HttpCheckerConfig) -> Result<hyper::Request<hyper::Body>, Reason> {
+    let mut builder = hyper::Request::builder();
+    builder
+        .method(service.method.clone())
+        .uri(service.url.clone());
+
+    for (name, value) in service.headers.iter() {
+        builder.header(name, value.clone());
+    }
+
+    match service.auth {
+        Some(Auth::Basic(ref basic_auth)) => {
+            let authorization_header_value = build_authorization_header_value(basic_auth);
+            builder.header(AUTHORIZATION, authorization_header_value);
+        }
+        Some(Auth::Bearer(ref value)) => {
+            builder.header(AUTHORIZATION, value.clone());
+        }
+        Some(Auth::Header(ref name, ref value)) => {
+            builder.header(name, value.clone());
+        }
+        // OAuth2 token is obtained asynchronously, see `check_http()`
+        Some(Auth::OAuth2(_)) | None => {}
+    }
+
+    let body = match service.body {
+        Some(ref body) => hyper::Body::from(body.clone()),
+        None => hyper::Body::empty(),
+    };
+
+    builder
+        .body(body)
+        .map_err(|err| Reason::Other(format!("Failed to build request: {}", err)))
+}
+
+fn authorize_with_token(
+    mut req: hyper::Request<hyper::Body>,
+    token: &str,
+) -> Result<hyper::Request<hyper::Body>, Reason> {
+    let value = HeaderValue::from_str(&format!("Bearer {}", token))
+        .map_err(|_| Reason::Auth("OAuth2 access token is not a valid header value".to_owned()))?;
+    req.headers_mut().insert(AUTHORIZATION, value);
+    Ok(req)
+}
+
+fn build_authorization_header_value(auth: &BasicAuth) -> HeaderValue {
+    let credentials = format!("{}:{}", auth.username, auth.password);
+    let encoded_credentials = base64::encode(&credentials);
+    let value = format!("Basic {}", encoded_credentials);
+    HeaderValue::from_str(&value).unwrap()
+}
+
+fn build_client() -> HttpsClient {
+    let https = resolver::build_connector().expect("TLS initialization failed");
+    Client::builder().build::<_, hyper::Body>(https)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::parse_config;
+    use tokio_core::reactor::Core;
+
+    use std::net::TcpListener;
+
+    fn run_check(core: &mut Core, yaml: &str) -> CheckResult {
+        let config = parse_config(yaml).unwrap();
+        let context = Context {
+            client: stub::client(core),
+            tokens: TokenCache::default(),
+            pool: CpuPool::new(1),
+        };
+        core.run(check::<()>(&context, &config.checkers[0]))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_timeout() {
+        // Connections are accepted by the OS, but nobody ever answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let yaml = format!(
+            "checkers:\n  api:\n    url: http://{}/\n    timeout: 200ms\n",
+            listener.local_addr().unwrap()
+        );
+        let mut core = Core::new().unwrap();
+
+        let started_at = Instant::now();
+        let result = run_check(&mut core, &yaml);
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+        assert_eq!(result.state, State::Down);
+        assert_eq!(
+            result.reason,
+            Some(Reason::Timeout(Duration::from_millis(200)))
+        );
+        assert_eq!(result.response_time, None);
+        assert_eq!(result.http_status, None);
+    }
+
+    #[test]
+    fn test_dns_error() {
+        // .invalid domain never resolves (RFC 6761)
+        let yaml = "checkers:\n  api:\n    url: http://hellcheck.invalid/\n    timeout: 5s\n";
+        let mut core = Core::new().unwrap();
+        match run_check(&mut core, yaml).reason {
+            Some(Reason::Dns(_)) => {}
+            reason => panic!("Expected DNS error, got {:?}", reason),
+        }
+    }
+
+    #[test]
+    fn test_connection_refused() {
+        // Nobody listens on the port, once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let yaml = format!("checkers:\n  api:\n    url: http://{}/\n", addr);
+        let mut core = Core::new().unwrap();
+        match run_check(&mut core, &yaml).reason {
+            Some(Reason::Connect(_)) => {}
+            reason => panic!("Expected connection error, got {:?}", reason),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use futures::future;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::rt::Future;
+use hyper::StatusCode;
+use serde_json::Value as JsonValue;
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::body::read_body;
+use super::HttpsClient;
+use crate::config::OAuth2Config;
+use crate::reactor::Reason;
+
+// Tokens are refreshed a bit before they expire, so a request never goes out with a stale one.
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(30);
+
+// Used when the token endpoint does not tell how long the token is valid.
+const DEFAULT_EXPIRES_IN: u64 = 3600;
+
+struct Token {
+    access_token: String,
+    expires_at: Instant,
+}
+
+// Token URL, client ID, scope and hash of the client secret.
+type CacheKey = (String, String, Option<String>, u64);
+
+// Caches OAuth2 access tokens, so they are not requested on every check.
+// Checkers with the same token URL, credentials and scope share the same token.
+#[derive(Clone, Default)]
+pub struct TokenCache {
+    tokens: Rc<RefCell<HashMap<CacheKey, Token>>>,
+}
+
+impl TokenCache {
+    pub fn access_token(
+        &self,
+        client: &HttpsClient,
+        config: &OAuth2Config,
+    ) -> Box<dyn Future<Item = String, Error = Reason>> {
+        let key = cache_key(config);
+
+        if let Some(token) = self.tokens.borrow().get(&key) {
+            if token.expires_at > Instant::now() {
+                return Box::new(future::ok(token.access_token.clone()));
+            }
+        }
+
+        let tokens = self.tokens.clone();
+        let f = fetch_token(client, config).map(move |token| {
+            let access_token = token.access_token.clone();
+            tokens.borrow_mut().insert(key, token);
+            access_token
+        });
+        Box::new(f)
+    }
+
+    pub fn invalidate(&self, config: &OAuth2Config) {
+        self.tokens.borrow_mut().remove(&cache_key(config));
+    }
+}
+
+fn cache_key(config: &OAuth2Config) -> CacheKey {
+    // The secret itself is not kept in the key
+    let mut hasher = DefaultHasher::new();
+    config.client_secret.hash(&mut hasher);
+    (
+        config.token_url.to_string(),
+        config.client_id.clone(),
+        config.scope.clone(),
+        hasher.finish(),
+    )
+}
+
+fn fetch_token(
+    client: &HttpsClient,
+    config: &OAuth2Config,
+) -> impl Future<Item = Token, Error = Reason> {
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    form.append_pair("grant_type", "client_credentials");
+    if let Some(ref scope) = config.scope {
+        form.append_pair("scope", scope);
+    }
+
+    let credentials = format!("{}:{}", config.client_id, config.client_secret);
+    let authorization = format!("Basic {}", base64::encode(&credentials));
+
+    let req = hyper::Request::post(config.token_url.clone())
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(AUTHORIZATION, authorization.as_str())
+        .body(hyper::Body::from(form.finish()));
+
+    let req = match req {
+        Ok(req) => req,
+        Err(err) => return future::Either::A(future::err(auth_error(err))),
+    };
+
+    let f = client
+        .request(req)
+        .and_then(|resp| {
+            let status = resp.status();
+            read_body(resp.into_body()).map(move |body| (status, body))
+        })
+        .map_err(auth_error)
+        .and_then(|(status, body)| parse_token_response(status, &body).map_err(auth_error));
+    future::Either::B(f)
+}
+
+fn parse_token_response(status: StatusCode, body: &[u8]) -> Result<Token, String> {
+    if !status.is_success() {
+        let mut message = format!("token endpoint responded with HTTP status {}", status);
+        // Error response of RFC 6749, e.g. `{"error": "invalid_client"}`
+        let json: JsonValue = serde_json::from_slice(body).unwrap_or_default();
+        if let Some(error) = json["error"].as_str() {
+            message.push_str(&format!(": {}", error));
+            if let Some(description) = json["error_description"].as_str() {
+                message.push_str(&format!(" ({})", description));
+            }
+        }
+        return Err(message);
+    }
+
+    let json: JsonValue = serde_json::from_slice(body)
+        .map_err(|err| format!("token endpoint responded with invalid JSON: {}", err))?;
+    let access_token = json["access_token"]
+        .as_str()
+        .ok_or_else(|| "token endpoint response has no `access_token`".to_owned())?;
+    let expires_in = json["expires_in"].as_u64().unwrap_or(DEFAULT_EXPIRES_IN);
+    let valid_for = Duration::from_secs(expires_in)
+        .checked_sub(EXPIRATION_MARGIN)
+        .unwrap_or_else(|| Duration::from_secs(0));
+
+    Ok(Token {
+        access_token: access_token.to_owned(),
+        expires_at: Instant::now() + valid_for,
+    })
+}
+
+fn auth_error<E: ToString>(err: E) -> Reason {
+    Reason::Auth(format!(
+        "Failed to obtain OAuth2 access token: {}",
+        err.to_string()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher::stub::{self, Stub, StubRequest};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio_core::reactor::Core;
+
+    fn parse(status: u16, body: &str) -> Result<Token, String> {
+        parse_token_response(StatusCode::from_u16(status).unwrap(), body.as_bytes())
+    }
+
+    #[test]
+    fn test_parse_token_response() {
+        let token = parse(200, r#"{"access_token": "abc", "expires_in": 130}"#).unwrap();
+        assert_eq!(token.access_token, "abc");
+        let valid_for = token.expires_at - Instant::now();
+        assert!(valid_for > Duration::from_secs(95) && valid_for <= Duration::from_secs(100));
+
+        // Default lifetime is used, if `expires_in` is missing
+        let token = parse(200, r#"{"access_token": "abc"}"#).unwrap();
+        assert!(token.expires_at > Instant::now() + Duration::from_secs(3500));
+
+        // Token, that expires within the margin, is used only once
+        let token = parse(200, r#"{"access_token": "abc", "expires_in": 10}"#).unwrap();
+        assert!(token.expires_at <= Instant::now());
+
+        assert_eq!(
+            parse(200, r#"{"token_type": "bearer"}"#).err().unwrap(),
+            "token endpoint response has no `access_token`"
+        );
+        assert!(parse(200, "<html>")
+            .err()
+            .unwrap()
+            .starts_with("token endpoint responded with invalid JSON"));
+        assert_eq!(
+            parse(500, "Internal Server Error").err().unwrap(),
+            "token endpoint responded with HTTP status 500 Internal Server Error"
+        );
+        assert_eq!(
+            parse(
+                401,
+                r#"{"error": "invalid_client", "error_description": "Unknown client"}"#
+            )
+            .err()
+            .unwrap(),
+            "token endpoint responded with HTTP status 401 Unauthorized: invalid_client (Unknown client)"
+        );
+    }
+
+    // Token endpoint, that issues tokens "token1", "token2", ... valid for `expires_in` seconds
+    fn token_endpoint(expires_in: u64) -> Stub {
+        let issued = Arc::new(AtomicUsize::new(0));
+        stub::serve(move |_: &StubRequest| {
+            let n = issued.fetch_add(1, Ordering::SeqCst) + 1;
+            let body = format!(
+                r#"{{"access_token": "token{}", "expires_in": {}}}"#,
+                n, expires_in
+            );
+            (
+                200,
+                vec![("Content-Type", "application/json".to_owned())],
+                body,
+            )
+        })
+    }
+
+    fn oauth2_config(server: &Stub, client_secret: &str, scope: Option<&str>) -> OAuth2Config {
+        OAuth2Config {
+            token_url: server.url("/token").parse().unwrap(),
+            client_id: "hellcheck".to_owned(),
+            client_secret: client_secret.to_owned(),
+            scope: scope.map(|s| s.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_token_request() {
+        let server = token_endpoint(3600);
+        let mut core = Core::new().unwrap();
+        let client = stub::client(&core);
+        let config = oauth2_config(&server, "secret", Some("health:read"));
+
+        let f = TokenCache::default().access_token(&client, &config);
+        assert_eq!(core.run(f), Ok("token1".to_owned()));
+
+        let req = &server.requests()[0];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/token");
+        assert_eq!(
+            req.header("authorization"),
+            Some(format!("Basic {}", base64::encode("hellcheck:secret")).as_ref())
+        );
+        assert_eq!(
+            req.header("content-type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert_eq!(
+            req.body,
+            "grant_type=client_credentials&scope=health%3Aread"
+        );
+    }
+
+    #[test]
+    fn test_token_cache() {
+        let server = token_endpoint(3600);
+        let mut core = Core::new().unwrap();
+        let client = stub::client(&core);
+        let tokens = TokenCache::default();
+        let mut token =
+            |config: &OAuth2Config| core.run(tokens.access_token(&client, config)).unwrap();
+
+        let config = oauth2_config(&server, "secret", None);
+        assert_eq!(token(&config), "token1");
+        assert_eq!(token(&config), "token1");
+
+        // Different scope or credentials need their own tokens
+        assert_eq!(
+            token(&oauth2_config(&server, "secret", Some("read"))),
+            "token2"
+        );
+        assert_eq!(token(&oauth2_config(&server, "other", None)), "token3");
+        assert_eq!(token(&config), "token1");
+
+        // Revoked token is obtained again
+        tokens.invalidate(&config);
+        assert_eq!(token(&config), "token4");
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        // Tokens expire within the margin, so every check needs a new one
+        let server = token_endpoint(10);
+        let mut core = Core::new().unwrap();
+        let client = stub::client(&core);
+        let tokens = TokenCache::default();
+        let config = oauth2_config(&server, "secret", None);
+
+        assert_eq!(
+            core.run(tokens.access_token(&client, &config)),
+            Ok("token1".to_owned())
+        );
+        assert_eq!(
+            core.run(tokens.access_token(&client, &config)),
+            Ok("token2".to_owned())
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use futures::future::{self, Loop};
+use hyper::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, HOST, LOCATION};
+use hyper::rt::Future;
+use hyper::{Body, Method, Request, Response, StatusCode, Uri};
+use url::Url;
+
+use super::{hyper_error_to_reason, HttpsClient};
+use crate::config::{Auth, HttpCheckerConfig};
+use crate::reactor::Reason;
+
+// Sends the request and follows redirects, if `follow_redirects` is enabled.
+// Resolves into the final response and the chain of requested URLs.
+pub fn send(
+    client: &HttpsClient,
+    service: &HttpCheckerConfig,
+    req: Request<Body>,
+) -> impl Future<Item = (Response<Body>, Vec<String>), Error = Reason> {
+    let client = client.clone();
+    let service = service.clone();
+    let chain = vec![normalize(req.uri())];
+
+    future::loop_fn((req, chain), move |(req, mut chain)| {
+        // Headers are needed to build the next request, since the body can't be cloned
+        let prev_method = req.method().clone();
+        let mut headers = req.headers().clone();
+        let service = service.clone();
+
+        client
+            .request(req)
+            .map_err(|err| hyper_error_to_reason(&err))
+            .and_then(move |resp| {
+                let status = resp.status();
+                let location = resp
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|val| val.to_str().ok())
+                    .map(|val| val.to_owned());
+                let location = match location {
+                    Some(ref location) if service.follow_redirects && is_redirect(status) => {
+                        location
+                    }
+                    _ => return Ok(Loop::Break((resp, chain))),
+                };
+
+                // unwrap is safe here, because the chain always has the requested URL
+                let next_url = resolve(chain.last().unwrap(), location)?;
+                let is_loop = chain.contains(&next_url);
+                chain.push(next_url.clone());
+                if is_loop {
+                    let message = format!("Redirect loop: {}", chain.join(" -> "));
+                    return Err(Reason::Redirect(message));
+                }
+                if chain.len() > service.max_redirects as usize + 1 {
+                    let message = format!(
+                        "More than {} redirects: {}",
+                        service.max_redirects,
+                        chain.join(" -> ")
+                    );
+                    return Err(Reason::Redirect(message));
+                }
+
+                // Configured headers may carry secrets (API keys, cookies), so neither they
+                // nor credentials are sent to other origins
+                let uri = next_url
+                    .parse::<Uri>()
+                    .map_err(|_| invalid_location(location))?;
+                if !same_origin(&uri, &service.url) {
+                    for name in service.headers.keys() {
+                        headers.remove(name);
+                    }
+                    headers.remove(HOST);
+                    headers.remove(AUTHORIZATION);
+                    if let Some(Auth::Header(ref name, _)) = service.auth {
+                        headers.remove(name);
+                    }
+                }
+
+                // Like browsers do, 303 (and 301/302 for POST) turns the request into GET
+                // without a body. 307 and 308 repeat the request as is.
+                let change_to_get = status == StatusCode::SEE_OTHER
+                    || (prev_method == Method::POST
+                        && (status == StatusCode::MOVED_PERMANENTLY
+                            || status == StatusCode::FOUND));
+                let (method, body) = if change_to_get {
+                    headers.remove(CONTENT_LENGTH);
+                    headers.remove(CONTENT_TYPE);
+                    (Method::GET, Body::empty())
+                } else {
+                    let body = match service.body {
+                        Some(ref body) => Body::from(body.clone()),
+                        None => Body::empty(),
+                    };
+                    (prev_method, body)
+                };
+
+                let mut next_req = Request::new(body);
+                *next_req.method_mut() = method;
+                *next_req.uri_mut() = uri;
+                *next_req.headers_mut() = headers;
+                Ok(Loop::Continue((next_req, chain)))
+            })
+    })
+}
+
+// Verifies that the redirects have ended up at the expected URL.
+pub fn verify_final_url(expected: &Uri, chain: &[String]) -> Result<(), Reason> {
+    let expected = normalize(expected);
+    // unwrap is safe here, because the chain always has the requested URL
+    let actual = chain.last().unwrap();
+    if *actual == expected {
+        return Ok(());
+    }
+    let message = format!(
+        "Expected final URL {}, got {} ({})",
+        expected,
+        actual,
+        chain.join(" -> ")
+    );
+    Err(Reason::Redirect(message))
+}
+
+fn is_redirect(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    )
+}
+
+// Location may be relative to the URL of the request.
+fn resolve(base: &str, location: &str) -> Result<String, Reason> {
+    Url::parse(base)
+        .and_then(|base| base.join(location))
+        .map(|url| url.to_string())
+        .map_err(|_| invalid_location(location))
+}
+
+// Makes URLs comparable, e.g. `http://example.com` and `http://example.com/` are the same.
+fn normalize(uri: &Uri) -> String {
+    let uri = uri.to_string();
+    Url::parse(&uri).map(|url| url.to_string()).unwrap_or(uri)
+}
+
+fn same_origin(a: &Uri, b: &Uri) -> bool {
+    a.scheme_part() == b.scheme_part() && a.authority_part() == b.authority_part()
+}
+
+fn invalid_location(location: &str) -> Reason {
+    Reason::Redirect(format!("Invalid redirect location `{}`", location))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parser::parse_config;
+    use crate::config::CheckerKind;
+    use crate::watcher::build_request;
+    use crate::watcher::stub::{self, StubResponse};
+    use tokio_core::reactor::Core;
+
+    // Parses config of the `api` checker with the given attributes
+    fn http_config(url: &str, attrs: &str) -> HttpCheckerConfig {
+        let yaml = format!(
+            "checkers:\n  api:\n    url: {}\n    follow_redirects: true\n{}",
+            url, attrs
+        );
+        match parse_config(&yaml).unwrap().checkers[0].kind {
+            CheckerKind::Http(ref http) => http.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn send_request(config: &HttpCheckerConfig) -> Result<(u16, Vec<String>), Reason> {
+        let mut core = Core::new().unwrap();
+        let client = stub::client(&core);
+        let req = build_request(config).unwrap();
+        let f = send(&client, config, req).map(|(resp, chain)| (resp.status().as_u16(), chain));
+        core.run(f)
+    }
+
+    fn redirect_to(status: u16, location: String) -> StubResponse {
+        (status, vec![("Location", location)], String::new())
+    }
+
+    fn ok() -> StubResponse {
+        (200, vec![], "OK".to_owned())
+    }
+
+    #[test]
+    fn test_cross_origin_redirect() {
+        let other = stub::serve(|_| ok());
+        let other_url = other.url("/landing");
+        let origin = stub::serve(move |req| match req.path.as_ref() {
+            "/start" => redirect_to(302, "/next".to_owned()),
+            _ => redirect_to(302, other_url.clone()),
+        });
+        let attrs = "    headers:
+      X-Api-Key: secret
+      Cookie: session=secret
+      Host: api.example.com
+    auth:
+      type: bearer
+      token: secret
+";
+        let config = http_config(&origin.url("/start"), attrs);
+        let (status, chain) = send_request(&config).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(
+            chain,
+            vec![
+                origin.url("/start"),
+                origin.url("/next"),
+                other.url("/landing")
+            ]
+        );
+        let final_url = other.url("/landing").parse::<Uri>().unwrap();
+        assert_eq!(verify_final_url(&final_url, &chain), Ok(()));
+
+        // Same origin gets everything
+        let requests = origin.requests();
+        assert_eq!(requests.len(), 2);
+        for req in requests.iter() {
+            assert_eq!(req.header("x-api-key"), Some("secret"));
+            assert_eq!(req.header("cookie"), Some("session=secret"));
+            assert_eq!(req.header("host"), Some("api.example.com"));
+            assert_eq!(req.header("authorization"), Some("Bearer secret"));
+        }
+
+        let requests = other.requests();
+        assert_eq!(requests.len(), 1);
+        let req = &requests[0];
+        assert_eq!(req.header("x-api-key"), None);
+        assert_eq!(req.header("cookie"), None);
+        assert_eq!(req.header("authorization"), None);
+        assert_eq!(req.header("host"), Some(other.addr.to_string().as_ref()));
+    }
+
+    #[test]
+    fn test_redirect_loop() {
+        let server = stub::serve(|req| match req.path.as_ref() {
+            "/a" => redirect_to(301, "/b".to_owned()),
+            _ => redirect_to(301, "/a".to_owned()),
+        });
+        let config = http_config(&server.url("/a"), "");
+        let expected = format!(
+            "Redirect loop: {} -> {} -> {}",
+            server.url("/a"),
+            server.url("/b"),
+            server.url("/a")
+        );
+        assert_eq!(send_request(&config), Err(Reason::Redirect(expected)));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_max_redirects() {
+        // /1 -> /2 -> /3 -> ...
+        let server = stub::serve(|req| {
+            let n: u32 = req.path[1..].parse().unwrap();
+            redirect_to(302, format!("/{}", n + 1))
+        });
+
+        let config = http_config(&server.url("/1"), "    max_redirects: 2\n");
+        let expected = format!(
+            "More than 2 redirects: {} -> {} -> {} -> {}",
+            server.url("/1"),
+            server.url("/2"),
+            server.url("/3"),
+            server.url("/4")
+        );
+        assert_eq!(send_request(&config), Err(Reason::Redirect(expected)));
+        assert_eq!(server.requests().len(), 3);
+
+        // Redirects are not followed unless enabled
+        let config = http_config(&server.url("/1"), "    follow_redirects: false\n");
+        let (status, chain) = send_request(&config).unwrap();
+        assert_eq!(status, 302);
+        assert_eq!(chain, vec![server.url("/1")]);
+    }
+
+    #[test]
+    fn test_redirect_method() {
+        let server = stub::serve(|req| match req.path.as_ref() {
+            "/see_other" => redirect_to(303, "/done".to_owned()),
+            "/temporary" => redirect_to(307, "/done".to_owned()),
+            _ => ok(),
+        });
+        let attrs = "    method: POST
+    headers:
+      Content-Type: application/json
+    body: '{\"query\": \"hellcheck\"}'
+";
+
+        // 303 turns the request into GET without a body
+        let config = http_config(&server.url("/see_other"), attrs);
+        assert_eq!(send_request(&config).unwrap().0, 200);
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[1].method, "GET");
+        assert_eq!(requests[1].path, "/done");
+        assert_eq!(requests[1].body, "");
+        assert_eq!(requests[1].header("content-type"), None);
+
+        // 307 repeats the request as is
+        server.requests.lock().unwrap().clear();
+        let config = http_config(&server.url("/temporary"), attrs);
+        assert_eq!(send_request(&config).unwrap().0, 200);
+        let requests = server.requests();
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].body, "{\"query\": \"hellcheck\"}");
+        assert_eq!(requests[1].header("content-type"), Some("application/json"));
+    }
+
+    #[test]
+    fn test_verify_final_url() {
+        let chain = vec![
+            "http://example.com/".to_owned(),
+            "https://www.example.com/".to_owned(),
+        ];
+        let expected = "https://www.example.com".parse::<Uri>().unwrap();
+        assert_eq!(verify_final_url(&expected, &chain), Ok(()));
+
+        let expected = "https://example.com/".parse::<Uri>().unwrap();
+        let message = "Expected final URL https://example.com/, got https://www.example.com/ \
+                       (http://example.com/ -> https://www.example.com/)";
+        assert_eq!(
+            verify_final_url(&expected, &chain),
+            Err(Reason::Redirect(message.to_owned()))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+// Hyper reports both failed DNS resolution and failed connection as a connect error caused by
+// io::Error. The resolver wraps its errors into ResolveError, so they can be told apart.
+
+use futures::future::MapErr;
+use hyper::client::connect::dns::{GaiAddrs, GaiFuture, GaiResolver, Name, Resolve};
+use hyper::client::connect::HttpConnector;
+use hyper::rt::Future;
+use hyper_tls::HttpsConnector;
+use native_tls::TlsConnector;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+pub type Connector = HttpsConnector<HttpConnector<Resolver>>;
+
+#[derive(Debug)]
+pub struct ResolveError(io::Error);
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for ResolveError {}
+
+#[derive(Debug, Clone)]
+pub struct Resolver(GaiResolver);
+
+impl Resolve for Resolver {
+    type Addrs = GaiAddrs;
+    type Future = MapErr<GaiFuture, fn(io::Error) -> io::Error>;
+
+    fn resolve(&self, name: Name) -> Self::Future {
+        self.0
+            .resolve(name)
+            .map_err(|err| io::Error::new(err.kind(), ResolveError(err)))
+    }
+}
+
+// The same as `HttpsConnector::new(1)`, but with the resolver above.
+pub fn build_connector() -> Result<Connector, native_tls::Error> {
+    let tls = TlsConnector::builder().build()?;
+    let mut http = HttpConnector::new_with_resolver(Resolver(GaiResolver::new(1)));
+    http.enforce_http(false);
+    Ok(HttpsConnector::from((http, tls)))
+}
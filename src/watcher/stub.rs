@@ -0,0 +1,134 @@
+// HTTP server for tests, that answers every request with the response returned by the handler.
+
+use hyper::{Body, Client};
+use tokio_core::reactor::Core;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::resolver::build_connector;
+use super::HttpsClient;
+
+// Response of the stub: status, extra headers and body.
+pub type StubResponse = (u16, Vec<(&'static str, String)>, String);
+
+// Request received by the stub.
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    pub path: String,
+    // Header names are lowercase
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_ref())
+    }
+}
+
+pub struct Stub {
+    pub addr: SocketAddr,
+    pub requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl Stub {
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// Client, whose connections are driven by the core instead of the background thread pool.
+pub fn client(core: &Core) -> HttpsClient {
+    let https = build_connector().unwrap();
+    Client::builder()
+        .executor(core.remote())
+        .build::<_, Body>(https)
+}
+
+pub fn serve<F>(handler: F) -> Stub
+where
+    F: Fn(&StubRequest) -> StubResponse + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = Arc::clone(&requests);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => break,
+            };
+            if let Some(request) = read_request(&stream) {
+                recorded.lock().unwrap().push(request.clone());
+                write_response(stream, handler(&request));
+            }
+        }
+    });
+
+    Stub { addr, requests }
+}
+
+fn read_request(stream: &TcpStream) -> Option<StubRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_owned();
+    let path = parts.next()?.to_owned();
+
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next()?.trim().to_lowercase();
+        let value = parts.next().unwrap_or("").trim().to_owned();
+        headers.push((name, value));
+    }
+
+    let length = headers
+        .iter()
+        .find(|(n, _)| n == "content-length")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(StubRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn write_response(mut stream: TcpStream, (status, headers, body): StubResponse) {
+    let mut response = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    let _ = stream.write_all(response.as_bytes());
+}
@@ -0,0 +1,46 @@
+use futures_cpupool::CpuPool;
+use hyper::rt::Future;
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::config::TcpCheckerConfig;
+use crate::reactor::Reason;
+
+// Verifies that the address accepts TCP connections. The connection is closed right away.
+pub fn check(
+    pool: &CpuPool,
+    config: &TcpCheckerConfig,
+    timeout: Duration,
+) -> impl Future<Item = (), Error = Reason> {
+    let host = config.host.clone();
+    let port = config.port;
+    pool.spawn_fn(move || connect(&host, port, timeout).map(|_| ()))
+}
+
+// Connects to the first resolved address, that accepts the connection.
+// Read and write operations on the stream time out after `timeout` as well.
+pub fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream, Reason> {
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| Reason::Dns(err.to_string()))?;
+
+    let mut last_err = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream
+                    .set_read_timeout(Some(timeout))
+                    .and_then(|()| stream.set_write_timeout(Some(timeout)))
+                    .map_err(|err| Reason::Connect(err.to_string()))?;
+                return Ok(stream);
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    match last_err {
+        Some(err) => Err(Reason::Connect(err.to_string())),
+        None => Err(Reason::Dns(format!("No addresses found for {}", host))),
+    }
+}
@@ -0,0 +1,225 @@
+use futures_cpupool::CpuPool;
+use hyper::rt::Future;
+use openssl::asn1::{Asn1Time, Asn1TimeRef};
+use openssl::error::ErrorStack;
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use openssl::x509::X509VerifyResult;
+
+use std::time::Duration;
+
+use super::tcp::connect;
+use crate::config::TlsCheckerConfig;
+use crate::reactor::Reason;
+
+// Codes of X509 verification errors, see `man 1 verify`.
+const X509_V_ERR_CERT_HAS_EXPIRED: i32 = 10;
+const X509_V_ERR_HOSTNAME_MISMATCH: i32 = 62;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Performs TLS handshake and verifies the certificate chain of the peer.
+// Resolves into number of days left until the first certificate in the chain expires
+// (if the chain was received) and result of the verification.
+pub fn check(
+    pool: &CpuPool,
+    config: &TlsCheckerConfig,
+    timeout: Duration,
+) -> impl Future<Item = (Option<i64>, Result<(), Reason>), Error = Reason> {
+    let config = config.clone();
+    pool.spawn_fn(move || inspect(&config, timeout))
+}
+
+fn inspect(
+    config: &TlsCheckerConfig,
+    timeout: Duration,
+) -> Result<(Option<i64>, Result<(), Reason>), Reason> {
+    let server_name = config.server_name.as_ref().unwrap_or(&config.host);
+    let stream = connect(&config.host, config.port, timeout)?;
+
+    let connector = SslConnector::builder(SslMethod::tls())
+        .map_err(|err| Reason::Tls(err.to_string()))?
+        .build();
+    let mut ssl_config = connector
+        .configure()
+        .map_err(|err| Reason::Tls(err.to_string()))?
+        .verify_hostname(true);
+    // Verification errors must not break the handshake, so the certificates can still be
+    // inspected. The result of the verification is examined after the handshake.
+    ssl_config.set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+
+    let ssl_stream = ssl_config
+        .connect(server_name, stream)
+        .map_err(|err| Reason::Tls(format!("Handshake failed: {}", err)))?;
+    let ssl = ssl_stream.ssl();
+
+    let now = Asn1Time::days_from_now(0).map_err(|err| Reason::Tls(err.to_string()))?;
+    let expiry_dates = ssl
+        .peer_cert_chain()
+        .into_iter()
+        .flat_map(|chain| chain.iter().map(|cert| cert.not_after()));
+    let secs_left =
+        secs_until_expiry(&now, expiry_dates).map_err(|err| Reason::Tls(err.to_string()))?;
+    let days_left = secs_left.map(days);
+
+    let verification = verify(
+        ssl.verify_result(),
+        server_name,
+        secs_left,
+        config.expiry_window,
+    );
+    Ok((days_left, verification))
+}
+
+// Seconds left until the earliest of the expiry dates (negative, if it has passed already).
+fn secs_until_expiry<'a, I>(now: &Asn1TimeRef, expiry_dates: I) -> Result<Option<i64>, ErrorStack>
+where
+    I: IntoIterator<Item = &'a Asn1TimeRef>,
+{
+    let mut secs_left: Option<i64> = None;
+    for not_after in expiry_dates {
+        let diff = now.diff(not_after)?;
+        let secs = i64::from(diff.days) * SECS_PER_DAY + i64::from(diff.secs);
+        secs_left = Some(secs_left.map_or(secs, |s| s.min(secs)));
+    }
+    Ok(secs_left)
+}
+
+// Number of whole days, rounded down (a certificate, that expired an hour ago, expired 1 day ago).
+fn days(secs: i64) -> i64 {
+    secs.div_euclid(SECS_PER_DAY)
+}
+
+fn verify(
+    verify_result: X509VerifyResult,
+    server_name: &str,
+    secs_left: Option<i64>,
+    expiry_window: Duration,
+) -> Result<(), Reason> {
+    match verify_result.as_raw() {
+        // Expiration is reported below together with the number of days
+        X509_V_ERR_CERT_HAS_EXPIRED => {}
+        X509_V_ERR_HOSTNAME_MISMATCH => {
+            let message = format!("Certificate does not match hostname {}", server_name);
+            return Err(Reason::Tls(message));
+        }
+        _ if verify_result != X509VerifyResult::OK => {
+            let message = format!(
+                "Invalid certificate chain: {}",
+                verify_result.error_string()
+            );
+            return Err(Reason::Tls(message));
+        }
+        _ => {}
+    }
+
+    let secs_left = match secs_left {
+        Some(secs) => secs,
+        None => return Err(Reason::Tls("No certificate was presented".to_owned())),
+    };
+    let days_left = days(secs_left);
+
+    if secs_left < 0 {
+        let message = format!("Certificate expired {} days ago", -days_left);
+        Err(Reason::Tls(message))
+    } else if secs_left < expiry_window.as_secs() as i64 {
+        let message = format!("Certificate expires in {} days", days_left);
+        Err(Reason::Tls(message))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60;
+
+    // 2019-02-05T03:00:00Z
+    fn now() -> Asn1Time {
+        Asn1Time::from_str("20190205030000Z").unwrap()
+    }
+
+    fn verify_result(code: i32) -> X509VerifyResult {
+        unsafe { X509VerifyResult::from_raw(code) }
+    }
+
+    #[test]
+    fn test_secs_until_expiry() {
+        let leaf = Asn1Time::from_str("20190206040000Z").unwrap();
+        let root = Asn1Time::from_str("20290101000000Z").unwrap();
+        let expired = Asn1Time::from_str("20190205020000Z").unwrap();
+
+        // The earliest expiry date counts
+        let dates = vec![root.as_ref(), leaf.as_ref()];
+        assert_eq!(secs_until_expiry(&now(), dates).unwrap(), Some(25 * HOUR));
+        let dates = vec![leaf.as_ref(), expired.as_ref()];
+        assert_eq!(secs_until_expiry(&now(), dates).unwrap(), Some(-HOUR));
+        assert_eq!(secs_until_expiry(&now(), vec![]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(days(25 * HOUR), 1);
+        assert_eq!(days(23 * HOUR), 0);
+        assert_eq!(days(0), 0);
+        assert_eq!(days(-HOUR), -1);
+        assert_eq!(days(-25 * HOUR), -2);
+    }
+
+    #[test]
+    fn test_verify() {
+        let window = Duration::from_secs(7 * 24 * 60 * 60);
+        let tls_error = |message: &str| Err(Reason::Tls(message.to_owned()));
+        let ok = X509VerifyResult::OK;
+        let expired = verify_result(X509_V_ERR_CERT_HAS_EXPIRED);
+        let mismatch = verify_result(X509_V_ERR_HOSTNAME_MISMATCH);
+
+        assert_eq!(
+            verify(ok, "example.com", Some(30 * 24 * HOUR), window),
+            Ok(())
+        );
+        // The expiry window is exclusive
+        assert_eq!(
+            verify(ok, "example.com", Some(7 * 24 * HOUR), window),
+            Ok(())
+        );
+        assert_eq!(
+            verify(ok, "example.com", Some(7 * 24 * HOUR - 1), window),
+            tls_error("Certificate expires in 6 days")
+        );
+        assert_eq!(
+            verify(ok, "example.com", Some(HOUR), window),
+            tls_error("Certificate expires in 0 days")
+        );
+        assert_eq!(
+            verify(expired, "example.com", Some(-25 * HOUR), window),
+            tls_error("Certificate expired 2 days ago")
+        );
+        assert_eq!(
+            verify(ok, "example.com", None, window),
+            tls_error("No certificate was presented")
+        );
+
+        // Hostname is verified before the expiry
+        assert_eq!(
+            verify(mismatch, "example.com", Some(-HOUR), window),
+            tls_error("Certificate does not match hostname example.com")
+        );
+        match verify(
+            verify_result(18),
+            "example.com",
+            Some(30 * 24 * HOUR),
+            window,
+        ) {
+            Err(Reason::Tls(ref message)) => {
+                assert!(
+                    message.starts_with("Invalid certificate chain: "),
+                    "{}",
+                    message
+                )
+            }
+            result => panic!("Expected invalid chain, got {:?}", result),
+        }
+    }
+}